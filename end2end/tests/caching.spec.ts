@@ -0,0 +1,21 @@
+import { test, expect } from "@playwright/test";
+
+test("pages are revalidated and compressed", async ({ request }) => {
+  const resp = await request.get("http://localhost:3000/", {
+    headers: { "Accept-Encoding": "gzip" },
+  });
+  const headers = resp.headers();
+
+  expect(headers["cache-control"]).toBe("no-cache");
+  expect(headers["content-encoding"]).toBe("gzip");
+});
+
+test("the bundle is compressed", async ({ request }) => {
+  const resp = await request.get("http://localhost:3000/pkg/vote.js", {
+    headers: { "Accept-Encoding": "br" },
+  });
+
+  expect(resp.ok()).toBeTruthy();
+  expect(resp.headers()["content-encoding"]).toBe("br");
+  expect(resp.headers()["vary"]).toContain("accept-encoding");
+});
@@ -0,0 +1,46 @@
+import { test, expect, type Page } from "@playwright/test";
+import { mockServerFn, topic } from "./support";
+
+/** Make the page believe it is `now`, still ticking unless `frozen` */
+async function fakeNow(page: Page, now: string, { frozen = false } = {}) {
+  await page.addInitScript(
+    ([now, frozen]) => {
+      const RealDate = Date;
+      const start = RealDate.now();
+      const fake = () => RealDate.parse(now) + (frozen ? 0 : RealDate.now() - start);
+      class FakeDate extends RealDate {
+        constructor(...args: []) {
+          super(...((args.length ? args : [fake()]) as []));
+        }
+        static now() {
+          return fake();
+        }
+      }
+      window.Date = FakeDate as DateConstructor;
+    },
+    [now, frozen] as const,
+  );
+}
+
+async function showTopic(page: Page, starts_at: string, ends_at: string) {
+  await mockServerFn(page, "get_one_topic", () => topic("1", { starts_at, ends_at }));
+  await mockServerFn(page, "get_vote_result", () => ({ "1-a": 0, "1-b": 0 }));
+  await page.goto("http://localhost:3000/topic/1");
+}
+
+test("the countdown shows days, hours, minutes and seconds", async ({ page }) => {
+  await fakeNow(page, "2030-01-01T00:00:00Z", { frozen: true });
+  await showTopic(page, "2030-01-03T03:04:05Z", "2030-01-10T00:00:00Z");
+
+  await expect(page.getByRole("timer")).toHaveText("Voting starts in 2d 03:04:05");
+});
+
+test("the countdown follows the clock into voting and past the end", async ({ page }) => {
+  await fakeNow(page, "2030-01-01T00:00:00Z");
+  await showTopic(page, "2030-01-01T00:00:02Z", "2030-01-01T00:00:04Z");
+
+  const timer = page.getByRole("timer");
+  await expect(timer).toHaveText(/^Voting starts in 00:00:0[0-2]$/);
+  await expect(timer).toHaveText(/^Voting closes in 00:00:0[0-2]$/);
+  await expect(timer).toHaveText("Voting has closed");
+});
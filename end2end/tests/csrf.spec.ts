@@ -0,0 +1,36 @@
+import { test, expect } from "@playwright/test";
+
+// the origin check runs before server fns are looked up, any path under /api works
+const api = "http://localhost:3000/api/any_server_fn";
+
+test("server fns reject requests from other sites", async ({ request }) => {
+  const resp = await request.post(api, {
+    headers: { Origin: "https://evil.example" },
+  });
+
+  expect(resp.status()).toBe(403);
+});
+
+test("server fns fall back to referer without origin", async ({ request }) => {
+  const resp = await request.post(api, {
+    headers: { Referer: "https://evil.example/page" },
+  });
+
+  expect(resp.status()).toBe(403);
+});
+
+test("server fns reject opaque origins", async ({ request }) => {
+  const resp = await request.post(api, {
+    headers: { Origin: "null" },
+  });
+
+  expect(resp.status()).toBe(403);
+});
+
+test("server fns accept requests from this site", async ({ request }) => {
+  const resp = await request.post(api, {
+    headers: { Origin: "http://localhost:3000" },
+  });
+
+  expect(resp.status()).not.toBe(403);
+});
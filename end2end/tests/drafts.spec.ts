@@ -0,0 +1,60 @@
+import { test, expect } from "@playwright/test";
+import { loginAs, mockServerFn, topic } from "./support";
+
+test("drafts need a login", async ({ page }) => {
+  await page.goto("http://localhost:3000/me/drafts");
+
+  await expect(page).toHaveURL(/\/login\?next=\/me\/drafts/);
+});
+
+test("drafts and scheduled topics are listed with their status", async ({ page }) => {
+  await mockServerFn(page, "get_drafts", () => [
+    topic("1", { description: "Lunch menu", draft: true, editors: ["bob"] }),
+    topic("2", { description: "Class trip", publish_at: "2030-01-01T08:00:00Z" }),
+  ]);
+  await loginAs(page, "alice", "organizer", "/me/drafts");
+
+  const draft = page.locator(".card", { hasText: "Lunch menu" });
+  await expect(draft).toContainText("Draft");
+  await expect(draft).toContainText("Co-editors: bob");
+  await expect(draft.getByRole("link", { name: "Edit" })).toHaveAttribute(
+    "href",
+    "/topic/create?draft=1",
+  );
+  await expect(page.locator(".card", { hasText: "Class trip" })).toContainText("Scheduled for");
+});
+
+test("publishing sends the topic and takes it off the list", async ({ page }) => {
+  let published: string | null = null;
+  await mockServerFn(page, "publish_topic", (args) => {
+    published = args.get("input[topic_id]");
+    return null;
+  });
+  await mockServerFn(page, "get_drafts", () =>
+    published ? [] : [topic("1", { description: "Lunch menu", draft: true })],
+  );
+  await loginAs(page, "alice", "organizer", "/me/drafts");
+
+  await page.getByRole("button", { name: "Publish now" }).click();
+
+  await expect(page.getByText("No drafts")).toBeVisible();
+  expect(published).toBe("1");
+});
+
+test("editors open their scheduled topics with their token", async ({ page }) => {
+  const tokens: (string | null)[] = [];
+  await mockServerFn(page, "get_one_topic", (args) => {
+    tokens.push(args.get("token"));
+    return topic("7", { description: "Class trip", publish_at: "2030-01-01T08:00:00Z" });
+  });
+  await mockServerFn(page, "get_vote_result", () => ({ "7-a": 0, "7-b": 0 }));
+  await mockServerFn(page, "get_my_vote", () => null);
+  await loginAs(page, "alice", "organizer", "/me");
+
+  // a client side navigation, the token only lives in the page
+  await page.evaluate(() => history.pushState({}, "", "/topic/7"));
+  await page.evaluate(() => window.dispatchEvent(new PopStateEvent("popstate")));
+
+  await expect(page.getByText("Class trip")).toBeVisible();
+  expect(tokens).toContain("token-alice");
+});
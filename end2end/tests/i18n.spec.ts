@@ -0,0 +1,49 @@
+import { test, expect } from "@playwright/test";
+
+test.describe("in Traditional Chinese", () => {
+  test.use({ extraHTTPHeaders: { "Accept-Language": "zh-TW,zh;q=0.9,en;q=0.8" } });
+
+  test("is negotiated from Accept-Language", async ({ page }) => {
+    await page.goto("http://localhost:3000/");
+
+    await expect(page.locator("html")).toHaveAttribute("lang", "zh-TW");
+    await expect(page).toHaveTitle("師大資工線上投票系統");
+    await expect(page.getByRole("link", { name: "議題" }).first()).toBeVisible();
+  });
+
+  test("the school login error is translated", async ({ page }) => {
+    await page.goto("http://localhost:3000/login?error=sso-failed");
+
+    await expect(page.locator(".alert-error")).toHaveText("學校帳號登入失敗");
+  });
+
+  test("the switcher overrides the header", async ({ page }) => {
+    await page.goto("http://localhost:3000/");
+
+    await page.getByRole("combobox", { name: "語言" }).selectOption("en");
+
+    await expect(page.locator("html")).toHaveAttribute("lang", "en");
+    await expect(page).toHaveTitle("NTNU CSIE Online Voting System");
+    await page.reload();
+    await expect(page.locator("html")).toHaveAttribute("lang", "en");
+  });
+});
+
+test("unsupported languages fall back to English", async ({ browser }) => {
+  const context = await browser.newContext({
+    extraHTTPHeaders: { "Accept-Language": "fr-FR,fr;q=0.9" },
+  });
+  const page = await context.newPage();
+  await page.goto("http://localhost:3000/login");
+
+  await expect(page.locator("html")).toHaveAttribute("lang", "en");
+  await expect(page.getByRole("heading", { name: "Login" })).toBeVisible();
+  await context.close();
+});
+
+test("only known login errors are shown", async ({ page }) => {
+  await page.goto("http://localhost:3000/login?error=Your%20account%20is%20locked");
+
+  await expect(page.getByRole("heading", { name: "Login" })).toBeVisible();
+  await expect(page.getByText("Your account is locked")).toHaveCount(0);
+});
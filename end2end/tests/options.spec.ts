@@ -0,0 +1,36 @@
+import { test, expect } from "@playwright/test";
+import { loginAs, mockServerFn } from "./support";
+
+test("reordered options are created in the new order", async ({ page }) => {
+  let created: URLSearchParams | null = null;
+  await mockServerFn(page, "get_templates", () => []);
+  await mockServerFn(page, "create_topic", (args) => {
+    created = args;
+    return null;
+  });
+  await loginAs(page, "alice", "organizer", "/topic/create");
+
+  await page.locator("#description").fill("Lunch menu");
+  await page.locator("#starts_at").fill("2030-01-01T08:00");
+  await page.locator("#ends_at").fill("2030-01-08T08:00");
+  const labels = page.locator('input[name="option-label[]"]');
+  await page.getByRole("button", { name: "Add Option" }).click();
+  // one option per pasted line
+  await labels.first().evaluate((input) => {
+    const clipboardData = new DataTransfer();
+    clipboardData.setData("text/plain", "Noodles\nRice\n\nSalad");
+    input.dispatchEvent(new ClipboardEvent("paste", { clipboardData, bubbles: true }));
+  });
+  await expect(labels).toHaveValues(["Noodles", "Rice", "Salad"]);
+
+  await page.getByRole("button", { name: "Move down" }).first().click();
+  await expect(labels).toHaveValues(["Rice", "Noodles", "Salad"]);
+  await page.getByRole("button", { name: /Drag to reorder/ }).nth(2).press("ArrowUp");
+  await expect(labels).toHaveValues(["Rice", "Salad", "Noodles"]);
+
+  await page.getByRole("button", { name: "Create" }).click();
+
+  await expect.poll(() => created?.get("input[options][0][label]")).toBe("Rice");
+  expect(created!.get("input[options][1][label]")).toBe("Salad");
+  expect(created!.get("input[options][2][label]")).toBe("Noodles");
+});
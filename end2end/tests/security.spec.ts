@@ -0,0 +1,56 @@
+import { test, expect } from "@playwright/test";
+
+test("pages are sent with security headers", async ({ request }) => {
+  const resp = await request.get("http://localhost:3000/");
+  const headers = resp.headers();
+
+  expect(headers["content-security-policy"]).toContain("default-src 'self'");
+  expect(headers["x-frame-options"]).toBe("DENY");
+  expect(headers["referrer-policy"]).toBe("strict-origin-when-cross-origin");
+  expect(headers["permissions-policy"]).toContain("camera=()");
+});
+
+test("inline scripts need the nonce of the response", async ({ request }) => {
+  const resp = await request.get("http://localhost:3000/");
+  const csp = resp.headers()["content-security-policy"];
+  const scriptSrc = csp.split(";").find((d) => d.trim().startsWith("script-src"));
+
+  expect(scriptSrc).not.toContain("'unsafe-inline'");
+  const nonce = scriptSrc?.match(/'nonce-([0-9a-f]+)'/)?.[1];
+  expect(nonce).toBeTruthy();
+
+  const html = await resp.text();
+  const scripts = html.match(/<script[^>]*>/g) ?? [];
+  expect(scripts.length).toBeGreaterThan(0);
+  for (const script of scripts) {
+    expect(script).toContain(`nonce=${nonce}`);
+  }
+});
+
+test("pages hydrate under the policy", async ({ page }) => {
+  const violations: string[] = [];
+  page.on("console", (msg) => {
+    if (msg.text().includes("Content Security Policy")) {
+      violations.push(msg.text());
+    }
+  });
+
+  await page.goto("http://localhost:3000/");
+  await page.waitForLoadState("networkidle");
+
+  expect(violations).toEqual([]);
+});
+
+test("styles are served by this site", async ({ page }) => {
+  const foreign: string[] = [];
+  page.on("request", (req) => {
+    if (!req.url().startsWith("http://localhost:3000/")) {
+      foreign.push(req.url());
+    }
+  });
+
+  await page.goto("http://localhost:3000/");
+
+  await expect(page.locator('link[href="/pkg/vote.css"]')).toHaveCount(1);
+  expect(foreign).toEqual([]);
+});
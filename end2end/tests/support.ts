@@ -0,0 +1,54 @@
+import type { Page, Request } from "@playwright/test";
+
+/**
+ * Answer the calls of the browser to the server fn `name` with `respond`, server fn
+ * URLs end in a hash, e.g. `/api/get_drafts1234`.
+ */
+export async function mockServerFn(
+  page: Page,
+  name: string,
+  respond: (args: URLSearchParams, request: Request) => unknown,
+) {
+  await page.route(new RegExp(`/api/${name}\\d+$`), async (route) => {
+    const args = new URLSearchParams(route.request().postData() ?? "");
+    const body = respond(args, route.request());
+    await route.fulfill({ contentType: "application/json", body: JSON.stringify(body) });
+  });
+}
+
+/** Log in as `username` through the single sign-on callback and go to `next` */
+export async function loginAs(
+  page: Page,
+  username: string,
+  role: "voter" | "organizer" | "admin",
+  next: string,
+) {
+  await mockServerFn(page, "get_me", () => ({ username, role }));
+  const hash = new URLSearchParams({ access_token: `token-${username}`, next });
+  await page.goto(`http://localhost:3000/login/oidc#${hash}`);
+  await page.waitForURL(`http://localhost:3000${next}`);
+}
+
+/** A topic as the backend sends it, with `fields` changed */
+export function topic(id: string, fields: Record<string, unknown> = {}) {
+  return {
+    id,
+    description: `Topic ${id}`,
+    starts_at: "2030-01-01T00:00:00Z",
+    ends_at: "2030-01-08T00:00:00Z",
+    created_at: "2029-12-01T00:00:00Z",
+    updated_at: "2029-12-01T00:00:00Z",
+    options: [
+      { id: `${id}-a`, label: "Yes", description: "" },
+      { id: `${id}-b`, label: "No", description: "" },
+    ],
+    stage: "upcoming",
+    tags: [],
+    draft: false,
+    publish_at: null,
+    creator: "alice",
+    editors: [],
+    attachments: [],
+    ...fields,
+  };
+}
@@ -0,0 +1,13 @@
+import { test, expect } from "@playwright/test";
+
+test("a tag page lists the topics with that tag", async ({ page }) => {
+  await page.goto("http://localhost:3000/tag/student-council");
+
+  await expect(page.locator("h2")).toHaveText("Topics tagged student-council");
+});
+
+test("non-ASCII tags are decoded from the URL", async ({ page }) => {
+  await page.goto("http://localhost:3000/tag/%E5%AD%B8%E7%94%9F%E6%9C%83");
+
+  await expect(page.locator("h2")).toHaveText("Topics tagged 學生會");
+});
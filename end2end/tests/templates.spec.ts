@@ -0,0 +1,86 @@
+import { test, expect } from "@playwright/test";
+import { loginAs, mockServerFn } from "./support";
+
+test.use({ timezoneId: "UTC" });
+
+const template = {
+  id: "t1",
+  name: "Semester survey",
+  description: "Which courses should be offered?",
+  options: [
+    { label: "Compilers", description: "", image: null },
+    { label: "Databases", description: "", image: null },
+  ],
+  tags: ["curriculum"],
+  starts_at: "2024-02-19T08:00:00Z",
+  ends_at: "2024-02-23T17:00:00Z",
+};
+
+test("duplicating a topic needs a login", async ({ page }) => {
+  await page.goto("http://localhost:3000/topic/create?from=1");
+
+  await expect(page).toHaveURL(/\/login\?next=/);
+});
+
+test("a template fills the form and a new start moves the end along", async ({ page }) => {
+  await mockServerFn(page, "get_templates", () => [template]);
+  await loginAs(page, "alice", "organizer", "/topic/create");
+
+  await page.locator("#template").selectOption({ label: "Semester survey" });
+  await page.getByRole("button", { name: "Use" }).click();
+
+  await expect(page.locator("#description")).toHaveValue("Which courses should be offered?");
+  await expect(page.locator('input[name="option-label[]"]')).toHaveValues([
+    "Compilers",
+    "Databases",
+  ]);
+  await expect(page.locator("#starts_at")).toHaveValue("2024-02-19T08:00");
+  await expect(page.locator("#ends_at")).toHaveValue("2024-02-23T17:00");
+
+  await page.locator("#starts_at").fill("2024-09-16T09:30");
+
+  await expect(page.locator("#ends_at")).toHaveValue("2024-09-20T18:30");
+});
+
+test("deleting a template asks first", async ({ page }) => {
+  const deleted: (string | null)[] = [];
+  await mockServerFn(page, "get_templates", () => [template]);
+  await mockServerFn(page, "delete_template", (args) => {
+    deleted.push(args.get("input[template_id]"));
+    return null;
+  });
+  await loginAs(page, "alice", "organizer", "/topic/create");
+  await page.locator("#template").selectOption({ label: "Semester survey" });
+
+  page.once("dialog", async (dialog) => {
+    expect(dialog.message()).toContain("Semester survey");
+    await dialog.dismiss();
+  });
+  await page.getByRole("button", { name: "Delete" }).click();
+  await expect(page.locator("#template")).toHaveValue("t1");
+  expect(deleted).toEqual([]);
+
+  page.once("dialog", (dialog) => dialog.accept());
+  await page.getByRole("button", { name: "Delete" }).click();
+  await expect.poll(() => deleted).toEqual(["t1"]);
+});
+
+test("the form is saved as a template", async ({ page }) => {
+  let saved: URLSearchParams | null = null;
+  await mockServerFn(page, "get_templates", () => []);
+  await mockServerFn(page, "create_template", (args) => {
+    saved = args;
+    return null;
+  });
+  await loginAs(page, "alice", "organizer", "/topic/create");
+
+  await page.locator("#description").fill("Lunch menu");
+  await page.getByRole("button", { name: "Add Option" }).click();
+  await page.locator('input[name="option-label[]"]').first().fill("Noodles");
+  await page.getByPlaceholder("Template name").fill("Weekly lunch");
+  await page.getByRole("button", { name: "Save as template" }).click();
+
+  await expect.poll(() => saved?.get("input[name]")).toBe("Weekly lunch");
+  expect(saved!.get("input[description]")).toBe("Lunch menu");
+  expect(saved!.get("input[options][0][label]")).toBe("Noodles");
+});
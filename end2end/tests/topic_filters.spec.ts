@@ -0,0 +1,28 @@
+import { test, expect } from "@playwright/test";
+
+test("filters are read from the URL", async ({ page }) => {
+  await page.goto("http://localhost:3000/topics?q=election&stage=voting&sort=ends");
+
+  await expect(page.locator('input[name="q"]')).toHaveValue("election");
+  await expect(page.getByRole("link", { name: "Voting" })).toHaveClass(/btn-primary/);
+  await expect(page.getByRole("link", { name: "Ending soon" })).toHaveClass(/btn-active/);
+});
+
+test("searching keeps the other filters", async ({ page }) => {
+  await page.goto("http://localhost:3000/topics?stage=ended");
+
+  await page.locator('input[name="q"]').fill("lunch");
+  await page.getByRole("button", { name: "Search" }).click();
+
+  await expect(page).toHaveURL(/q=lunch/);
+  await expect(page).toHaveURL(/stage=ended/);
+});
+
+test("changing the page keeps the filters", async ({ page }) => {
+  await page.goto("http://localhost:3000/topics?stage=ended&page=2");
+
+  await page.getByRole("button", { name: "«" }).click();
+
+  await expect(page).toHaveURL(/stage=ended/);
+  await expect(page).toHaveURL(/page=1/);
+});
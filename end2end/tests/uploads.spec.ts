@@ -0,0 +1,29 @@
+import { test, expect } from "@playwright/test";
+
+test("uploads need a logged in organizer", async ({ request }) => {
+  const resp = await request.post("http://localhost:3000/upload", {
+    multipart: {
+      file: {
+        name: "logo.png",
+        mimeType: "image/png",
+        buffer: Buffer.from("not really a picture"),
+      },
+    },
+  });
+
+  expect(resp.status()).toBe(401);
+});
+
+test("unknown uploads are not found", async ({ request }) => {
+  const resp = await request.get(
+    "http://localhost:3000/uploads/00000000-0000-0000-0000-000000000000.png",
+  );
+
+  expect(resp.status()).toBe(404);
+});
+
+test("only stored file names are looked up", async ({ request }) => {
+  const resp = await request.get("http://localhost:3000/uploads/.hidden.png");
+
+  expect(resp.status()).toBe(404);
+});
@@ -0,0 +1,251 @@
+# English messages of the UI, see `src/i18n.rs`
+
+site-title = NTNU CSIE Online Voting System
+
+## Navigation bar
+
+nav-home = Voting System
+nav-login = Login
+nav-topics = Topics
+nav-new-topic = New Topic
+nav-drafts = Drafts
+nav-moderation = Moderation
+nav-tags = Tags
+nav-about = About
+nav-language = Language
+
+## Shared
+
+loading = Loading...
+submit = Submit
+login-to-continue = Login to continue
+error-title = Error Occurred!
+cooldown = Too many attempts, please try again in { $seconds } seconds.
+guard-role-required = You need the { $role } role to access this page.
+pagination = Page { $page } / { $count }
+
+## Comments
+
+comment-create-title = Leave your comment here:
+comment-report = Report
+comment-report-reason = Why should this comment be reviewed?
+comment-report-submit = Submit report
+comment-report-failed = Report failed!
+comment-reported = Reported. A moderator will review this comment.
+comment-report-reason-required = Please tell why this comment should be reviewed.
+comment-reaction-failed = Reaction failed!
+comment-reaction-unknown = This reaction is not available.
+close = Close
+
+## Countdown
+
+countdown-days = { $days }d
+countdown-starts-in = Voting starts in { $remaining }
+countdown-closes-in = Voting closes in { $remaining }
+countdown-closed = Voting has closed
+
+## Uploads
+
+upload-uploading = Uploading...
+upload-option-picture = Option picture
+upload-remove-picture = Remove picture
+upload-remove-document = Remove document
+upload-documents = Documents
+
+## Topic cards and filters
+
+topic-detail = Detail
+topic-starts-at = Starts at:{" "}
+topic-ends-at = Ends at:{" "}
+topic-updated-at = Updated at:{" "}
+topic-search-placeholder = Search descriptions and options
+topic-search = Search
+
+## Tags
+
+tag-remove = Remove tag { $tag }
+tag-placeholder = Add a tag, e.g. student-council
+
+## Home
+
+home-voting = Voting now
+home-voting-empty = Nothing is being voted on right now
+home-upcoming = Upcoming
+home-upcoming-empty = No upcoming topics
+home-all-topics = All topics
+home-all-topics-count = { $count ->
+    [one] All { $count } topic
+   *[other] All { $count } topics
+}
+
+## Login and signup
+
+login = Login
+login-or = or
+login-sso = Login with school account
+login-sso-failed = Login with school account failed
+login-username = Username / Email
+login-no-account = Don't have an account?
+login-forgot-password = Forgot password?
+login-failed = Login failed!
+login-logging-in = Logging in...
+signup = Signup
+signup-have-account = Already have an account?
+signup-username-taken = Username is already taken
+signup-email-taken = Email is already used by another account
+signup-failed = Signup failed!
+signup-disabled = Signup is disabled
+username = Username
+email = Email
+password = Password
+password-confirm = Confirm Password
+password-mismatch = Passwords do not match
+password-weak = Weak
+password-fair = Fair
+password-good = Good
+password-strong = Strong
+
+## Validation
+
+invalid-username-length = Username must be 3 to 32 characters long
+invalid-username-start = Username must start with a letter
+invalid-username-chars = Username may only contain letters, digits, '_' and '-'
+invalid-email = Email is not valid
+invalid-password-length = Password must be at least 8 characters long
+invalid-password-weak = Password is too weak, mix letters, digits and symbols
+
+## Account
+
+account-change-password = Change Password
+account-current-password = Current Password
+account-new-password = New Password
+account-confirm-new-password = Confirm New Password
+account-change-password-failed = Change password failed!
+account-password-changed = Your password has been changed.
+account-forgot-password = Forgot Password
+account-send-reset-link = Send Reset Link
+account-request-reset-failed = Request password reset failed!
+account-reset-link-sent = If the email belongs to an account, a reset link is on its way.
+account-reset-password = Reset Password
+account-reset-password-failed = Reset password failed!
+account-password-reset = Your password has been reset.
+account-verify-email = Verify Email
+account-verify-email-failed = Verify email failed!
+account-email-verified = Your email has been verified.
+account-resend-prompt = Did not get the mail? Send it again:
+account-resend-verification = Resend Verification Mail
+account-resend-failed = Resend verification mail failed!
+account-verification-sent = Verification mail sent.
+
+## Profile
+
+profile-change-password = Change password
+profile-my-topics = My Topics
+profile-no-topics = No topics
+profile-my-votes = My Votes
+profile-no-votes = No votes
+profile-vote-hidden = (hidden)
+profile-voted = Voted: { $choice }
+profile-my-comments = My Comments
+profile-no-comments = No comments
+
+## Roles and stages
+
+role-voter = voter
+role-organizer = organizer
+role-admin = admin
+stage-upcoming = Upcoming
+stage-voting = Voting
+stage-ended = Ended
+sort-created = Newest
+sort-ends = Ending soon
+sort-participation = Most votes
+
+## Moderation
+
+moderation-title = Moderation Queue
+moderation-login = Login to review reports.
+moderation-empty = No reported comments
+moderation-reason = Reason:{" "}
+moderation-reported-by = Reported by:{" "}
+moderation-reported-at = Reported at:{" "}
+moderation-keep = Keep
+moderation-remove = Remove
+moderation-resolve-failed = Resolve report failed!
+
+## Tag administration
+
+tags-empty = No tags yet
+tags-tag = Tag
+tags-select = Select { $tag }
+tags-new-name = New name of { $tag }
+tags-rename = Rename
+tags-merge-into = Merge selected into
+tags-merge = Merge
+tags-rename-failed = Rename tag failed!
+tags-merge-failed = Merge tags failed!
+
+## Topics
+
+topics-empty = No topics found
+topics-tagged = Topics tagged { $tag }
+topic-page = Topic Page
+topic-no-comments = No comments
+topic-comments = Comments
+topic-votes = { $count ->
+    [one] { $formatted } vote
+   *[other] { $formatted } votes
+}
+topic-vote-not-open = Not open yet
+topic-vote-closed = Closed
+topic-vote-failed = Your vote was not counted
+topic-duplicate = Duplicate
+topic-login-to-vote = Login to vote.
+
+## Topic form
+
+topic-form-create = Create Topic
+topic-form-edit-draft = Edit Draft
+topic-form-description = Description
+topic-form-time-zone = Times are in UTC{ $offset }
+topic-form-starts-at = Starts At
+topic-form-ends-at = Ends At
+topic-form-publish-at = Publish At (optional, right away if empty)
+topic-form-documents = Documents (PDF)
+topic-form-editors = Co-editors
+topic-form-editors-placeholder = Usernames, separated by commas
+topic-form-options = Options
+topic-form-drag = Drag to reorder, or press the up and down arrow keys
+topic-form-option-label = Option Label, paste lines to add several
+topic-form-option-description = Option Description
+topic-form-move-up = Move up
+topic-form-move-down = Move down
+topic-form-remove-option = Remove option
+topic-form-add-option = Add Option
+topic-form-save-draft = Save Draft
+topic-form-submit = Create
+topic-form-schedule = Schedule
+topic-form-failed = Create Topic Failed
+
+## Drafts
+
+drafts-empty = No drafts
+drafts-draft = Draft
+drafts-scheduled = Scheduled for { $time }
+drafts-publishing = Publishing
+drafts-editors = Co-editors: { $editors }
+drafts-created-by = Created by:{" "}
+drafts-edit = Edit
+drafts-publish = Publish now
+drafts-publish-failed = Publish failed!
+
+## Templates
+
+template = Template
+template-pick = Pick a template
+template-use = Use
+template-delete = Delete
+template-delete-confirm = Delete the template “{ $name }”? This cannot be undone.
+template-name = Template name
+template-save = Save as template
+template-failed = Template failed
@@ -0,0 +1,245 @@
+# Traditional Chinese messages of the UI, see `src/i18n.rs`
+
+site-title = 師大資工線上投票系統
+
+## Navigation bar
+
+nav-home = 投票系統
+nav-login = 登入
+nav-topics = 議題
+nav-new-topic = 新增議題
+nav-drafts = 草稿
+nav-moderation = 檢舉管理
+nav-tags = 標籤
+nav-about = 關於
+nav-language = 語言
+
+## Shared
+
+loading = 載入中...
+submit = 送出
+login-to-continue = 登入以繼續
+error-title = 發生錯誤！
+cooldown = 嘗試次數過多，請於 { $seconds } 秒後再試。
+guard-role-required = 你需要「{ $role }」身分才能瀏覽此頁面。
+pagination = 第 { $page } / { $count } 頁
+
+## Comments
+
+comment-create-title = 在這裡留下你的留言：
+comment-report = 檢舉
+comment-report-reason = 為什麼這則留言需要審查？
+comment-report-submit = 送出檢舉
+comment-report-failed = 檢舉失敗！
+comment-reported = 已檢舉，管理員將會審查這則留言。
+comment-report-reason-required = 請說明這則留言需要審查的原因。
+comment-reaction-failed = 表情回應失敗！
+comment-reaction-unknown = 無法使用這個表情回應。
+close = 關閉
+
+## Countdown
+
+countdown-days = { $days } 天
+countdown-starts-in = 距離投票開始還有 { $remaining }
+countdown-closes-in = 距離投票結束還有 { $remaining }
+countdown-closed = 投票已結束
+
+## Uploads
+
+upload-uploading = 上傳中...
+upload-option-picture = 選項圖片
+upload-remove-picture = 移除圖片
+upload-remove-document = 移除文件
+upload-documents = 文件
+
+## Topic cards and filters
+
+topic-detail = 詳細資訊
+topic-starts-at = 開始時間：
+topic-ends-at = 結束時間：
+topic-updated-at = 更新時間：
+topic-search-placeholder = 搜尋議題說明與選項
+topic-search = 搜尋
+
+## Tags
+
+tag-remove = 移除標籤 { $tag }
+tag-placeholder = 新增標籤，例如 student-council
+
+## Home
+
+home-voting = 投票進行中
+home-voting-empty = 目前沒有進行中的投票
+home-upcoming = 即將開始
+home-upcoming-empty = 沒有即將開始的議題
+home-all-topics = 所有議題
+home-all-topics-count = 全部 { $count } 個議題
+
+## Login and signup
+
+login = 登入
+login-or = 或
+login-sso = 使用學校帳號登入
+login-sso-failed = 學校帳號登入失敗
+login-username = 使用者名稱 / 電子郵件
+login-no-account = 還沒有帳號嗎？
+login-forgot-password = 忘記密碼？
+login-failed = 登入失敗！
+login-logging-in = 登入中...
+signup = 註冊
+signup-have-account = 已經有帳號了嗎？
+signup-username-taken = 此使用者名稱已被使用
+signup-email-taken = 此電子郵件已被其他帳號使用
+signup-failed = 註冊失敗！
+signup-disabled = 目前不開放註冊
+username = 使用者名稱
+email = 電子郵件
+password = 密碼
+password-confirm = 確認密碼
+password-mismatch = 兩次輸入的密碼不一致
+password-weak = 弱
+password-fair = 普通
+password-good = 良好
+password-strong = 強
+
+## Validation
+
+invalid-username-length = 使用者名稱長度須為 3 到 32 個字元
+invalid-username-start = 使用者名稱必須以英文字母開頭
+invalid-username-chars = 使用者名稱只能包含英文字母、數字、「_」與「-」
+invalid-email = 電子郵件格式不正確
+invalid-password-length = 密碼長度至少須為 8 個字元
+invalid-password-weak = 密碼強度不足，請混用英文字母、數字與符號
+
+## Account
+
+account-change-password = 變更密碼
+account-current-password = 目前密碼
+account-new-password = 新密碼
+account-confirm-new-password = 確認新密碼
+account-change-password-failed = 變更密碼失敗！
+account-password-changed = 你的密碼已變更。
+account-forgot-password = 忘記密碼
+account-send-reset-link = 寄送重設連結
+account-request-reset-failed = 申請重設密碼失敗！
+account-reset-link-sent = 若此電子郵件屬於某個帳號，重設連結已寄出。
+account-reset-password = 重設密碼
+account-reset-password-failed = 重設密碼失敗！
+account-password-reset = 你的密碼已重設。
+account-verify-email = 驗證電子郵件
+account-verify-email-failed = 驗證電子郵件失敗！
+account-email-verified = 你的電子郵件已驗證。
+account-resend-prompt = 沒有收到信嗎？重新寄送：
+account-resend-verification = 重新寄送驗證信
+account-resend-failed = 重新寄送驗證信失敗！
+account-verification-sent = 驗證信已寄出。
+
+## Profile
+
+profile-change-password = 變更密碼
+profile-my-topics = 我的議題
+profile-no-topics = 沒有議題
+profile-my-votes = 我的投票
+profile-no-votes = 沒有投票
+profile-vote-hidden = （不公開）
+profile-voted = 已投：{ $choice }
+profile-my-comments = 我的留言
+profile-no-comments = 沒有留言
+
+## Roles and stages
+
+role-voter = 投票者
+role-organizer = 主辦者
+role-admin = 管理員
+stage-upcoming = 即將開始
+stage-voting = 投票中
+stage-ended = 已結束
+sort-created = 最新
+sort-ends = 即將結束
+sort-participation = 最多投票
+
+## Moderation
+
+moderation-title = 檢舉審查
+moderation-login = 登入以審查檢舉。
+moderation-empty = 沒有被檢舉的留言
+moderation-reason = 原因：
+moderation-reported-by = 檢舉者：
+moderation-reported-at = 檢舉時間：
+moderation-keep = 保留
+moderation-remove = 移除
+moderation-resolve-failed = 處理檢舉失敗！
+
+## Tag administration
+
+tags-empty = 尚無標籤
+tags-tag = 標籤
+tags-select = 選取 { $tag }
+tags-new-name = { $tag } 的新名稱
+tags-rename = 重新命名
+tags-merge-into = 將選取的標籤合併為
+tags-merge = 合併
+tags-rename-failed = 重新命名標籤失敗！
+tags-merge-failed = 合併標籤失敗！
+
+## Topics
+
+topics-empty = 找不到議題
+topics-tagged = 標籤為「{ $tag }」的議題
+topic-page = 議題頁面
+topic-no-comments = 沒有留言
+topic-comments = 留言
+topic-votes = { $formatted } 票
+topic-vote-not-open = 尚未開放
+topic-vote-closed = 已截止
+topic-vote-failed = 投票未成功
+topic-duplicate = 複製
+topic-login-to-vote = 登入以投票。
+
+## Topic form
+
+topic-form-create = 建立議題
+topic-form-edit-draft = 編輯草稿
+topic-form-description = 說明
+topic-form-time-zone = 時間以 UTC{ $offset } 為準
+topic-form-starts-at = 開始時間
+topic-form-ends-at = 結束時間
+topic-form-publish-at = 發布時間（選填，留空則立即發布）
+topic-form-documents = 文件（PDF）
+topic-form-editors = 共同編輯者
+topic-form-editors-placeholder = 使用者名稱，以逗號分隔
+topic-form-options = 選項
+topic-form-drag = 拖曳以重新排序，或按上下方向鍵
+topic-form-option-label = 選項名稱，貼上多行可一次新增多個
+topic-form-option-description = 選項說明
+topic-form-move-up = 上移
+topic-form-move-down = 下移
+topic-form-remove-option = 移除選項
+topic-form-add-option = 新增選項
+topic-form-save-draft = 儲存草稿
+topic-form-submit = 建立
+topic-form-schedule = 排程發布
+topic-form-failed = 建立議題失敗
+
+## Drafts
+
+drafts-empty = 沒有草稿
+drafts-draft = 草稿
+drafts-scheduled = 預定於 { $time } 發布
+drafts-publishing = 發布中
+drafts-editors = 共同編輯者：{ $editors }
+drafts-created-by = 建立者：
+drafts-edit = 編輯
+drafts-publish = 立即發布
+drafts-publish-failed = 發布失敗！
+
+## Templates
+
+template = 範本
+template-pick = 選擇範本
+template-use = 套用
+template-delete = 刪除
+template-delete-confirm = 確定要刪除範本「{ $name }」嗎？刪除後無法復原。
+template-name = 範本名稱
+template-save = 另存為範本
+template-failed = 範本操作失敗
@@ -0,0 +1,29 @@
+#!/bin/sh
+# Fingerprints and precompresses the bundle, run after `cargo leptos build --release`.
+#
+# The JS, WASM and CSS are copied to `<name>.<hash>.*`, with one hash over all three,
+# and the hash is written to `pkg/hash` for the server to pick up. Then every JS, WASM
+# and CSS file in `pkg` gets a brotli and a gzip copy next to it.
+#
+# usage: scripts/build-assets.sh [pkg dir, default target/site/pkg]
+set -eu
+
+pkg=${1:-target/site/pkg}
+name=${LEPTOS_OUTPUT_NAME:-vote}
+
+# cargo-leptos names the WASM `<name>.wasm`, wasm-pack `<name>_bg.wasm`
+wasm=$name.wasm
+[ -f "$pkg/$wasm" ] || wasm=${name}_bg.wasm
+
+hash=$(cat "$pkg/$name.js" "$pkg/$wasm" "$pkg/$name.css" | sha256sum | cut -c 1-16)
+cp "$pkg/$name.js" "$pkg/$name.$hash.js"
+cp "$pkg/$name.css" "$pkg/$name.$hash.css"
+cp "$pkg/$wasm" "$pkg/$name.$hash${wasm#"$name"}"
+echo "$hash" > "$pkg/hash"
+
+find "$pkg" -type f \( -name '*.js' -o -name '*.wasm' -o -name '*.css' \) | while read -r file; do
+    gzip -9 -k -f "$file"
+    brotli -f -q 11 "$file"
+done
+
+echo "assets hashed $hash"
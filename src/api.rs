@@ -1,17 +1,202 @@
 use leptos::{ServerFnError, *};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::OnceLock};
+use std::collections::HashMap;
+use std::str::FromStr;
 
-// base url is not used in client code
+// base url and client are not used in client code
 
-#[allow(unused)]
-static BASE_URL: OnceLock<String> = OnceLock::new();
+#[cfg(feature = "ssr")]
+pub(crate) fn base_url() -> &'static str {
+    &crate::server::config::config().backend.url
+}
 
-#[allow(unused)]
-fn base_url() -> &'static str {
-    BASE_URL.get_or_init(|| {
-        std::env::var("VOTE_BACKEND_URL").unwrap_or("http://localhost:8000".to_string())
-    })
+/// Client for backend calls, with the configured timeouts
+#[cfg(feature = "ssr")]
+pub(crate) fn client() -> reqwest::Client {
+    use std::sync::OnceLock;
+
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            let backend = &crate::server::config::config().backend;
+            reqwest::Client::builder()
+                .timeout(backend.timeout())
+                .connect_timeout(backend.connect_timeout())
+                .build()
+                .expect("backend client should build")
+        })
+        .clone()
+}
+
+/// Sending backend requests with tracing
+#[cfg(feature = "ssr")]
+#[async_trait::async_trait]
+pub(crate) trait SendExt {
+    /// Like `send`, but passes on the request id and logs URL, status and latency
+    async fn send_traced(self) -> reqwest::Result<reqwest::Response>;
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait::async_trait]
+impl SendExt for reqwest::RequestBuilder {
+    async fn send_traced(self) -> reqwest::Result<reqwest::Response> {
+        use crate::server::telemetry::{current_request_id, redact, REQUEST_ID_HEADER};
+        use tracing::Instrument;
+
+        let (client, req) = self.build_split();
+        let mut req = req?;
+        if let Some(id) = current_request_id().and_then(|id| id.parse().ok()) {
+            req.headers_mut().insert(REQUEST_ID_HEADER, id);
+        }
+
+        let span = tracing::info_span!(
+            "backend",
+            method = %req.method(),
+            url = %redact(req.url().as_str()),
+        );
+        async move {
+            let started_at = std::time::Instant::now();
+            let method = req.method().to_string();
+            let result = client.execute(req).await;
+            let latency = started_at.elapsed();
+            let latency_ms = latency.as_millis() as u64;
+            crate::server::metrics::metrics().backend_call(
+                &method,
+                result.as_ref().ok().map(|r| r.status().as_u16()),
+                latency,
+            );
+            match &result {
+                Ok(resp) if resp.status().is_success() => {
+                    tracing::info!(status = resp.status().as_u16(), latency_ms, "backend call")
+                }
+                Ok(resp) => {
+                    tracing::warn!(status = resp.status().as_u16(), latency_ms, "backend call")
+                }
+                Err(e) => tracing::error!(latency_ms, "backend call failed: {e}"),
+            }
+
+            result
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+/// One page of a list returned by the backend
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Number of items across all pages
+    pub total: usize,
+    /// 1-based page number
+    pub page: usize,
+    pub page_size: usize,
+}
+
+impl<T> Paginated<T> {
+    pub fn page_count(&self) -> usize {
+        if self.page_size == 0 {
+            return 0;
+        }
+        (self.total + self.page_size - 1) / self.page_size
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PageInput {
+    /// 1-based page number
+    pub page: usize,
+    pub page_size: usize,
+}
+
+impl Default for PageInput {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            page_size: 10,
+        }
+    }
+}
+
+/// A picture uploaded through `/upload`, see `server::upload`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Image {
+    pub url: String,
+    /// At most `THUMBNAIL_SIZE` pixels wide and high
+    pub thumbnail_url: String,
+}
+
+/// A PDF document attached to a topic, offered as a download
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Attachment {
+    pub url: String,
+    /// File name it was uploaded with
+    pub name: String,
+    /// In bytes
+    pub size: u64,
+}
+
+/// Longest side of image thumbnails, in pixels
+pub const THUMBNAIL_SIZE: u32 = 320;
+
+/// Names handed out by `/upload`, anything else (e.g. `..`) is never looked up
+pub fn is_upload_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+/// Name of the stored file `url` points to, `None` unless it is a `/uploads/` URL, so
+/// that no other URL (e.g. `javascript:`) ends up in a link or picture
+pub fn upload_name(url: &str) -> Option<&str> {
+    url.strip_prefix("/uploads/")
+        .filter(|name| is_upload_name(name))
+}
+
+/// Stored files an attachment list and options refer to
+///
+/// Fails if any of them does not point to `/uploads/`.
+#[cfg(feature = "ssr")]
+fn upload_names<'a>(
+    attachments: &'a [Attachment],
+    options: &'a [CreateOptionInput],
+) -> Result<Vec<&'a str>, ServerFnError> {
+    let images = options.iter().filter_map(|opt| opt.image.as_ref());
+    attachments
+        .iter()
+        .map(|a| &a.url)
+        .chain(images.flat_map(|image| [&image.url, &image.thumbnail_url]))
+        .map(|url| {
+            upload_name(url)
+                .ok_or_else(|| ServerFnError::ServerError(format!("invalid upload {url:?}")))
+        })
+        .collect()
+}
+
+/// Keep the uploads of a topic or template from being swept, see `server::upload`
+#[cfg(feature = "ssr")]
+async fn claim_uploads(
+    attachments: &[Attachment],
+    options: &[CreateOptionInput],
+) -> Result<(), ServerFnError> {
+    let storage = crate::server::upload::storage();
+    for name in upload_names(attachments, options)? {
+        storage.claim(name).await.map_err(|e| {
+            tracing::error!("{e:#}");
+            ServerFnError::ServerError("cannot keep uploads".to_string())
+        })?;
+    }
+
+    Ok(())
+}
+
+/// What `/upload` answers with, `image` is only set for pictures
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Upload {
+    pub attachment: Attachment,
+    pub image: Option<Image>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -19,6 +204,8 @@ pub struct VoteOption {
     pub id: String,
     pub label: String,
     pub description: String,
+    #[serde(default)]
+    pub image: Option<Image>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -31,36 +218,395 @@ pub struct Topic {
     pub updated_at: String,
     pub options: Vec<VoteOption>,
     pub stage: String,
+    /// Committees or categories, see [`normalize_tag`]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only the creator and editors see a draft
+    #[serde(default)]
+    pub draft: bool,
+    /// When a topic that is not a draft becomes visible to everyone, right away if `None`
+    #[serde(default)]
+    pub publish_at: Option<String>,
+    #[serde(default)]
+    pub creator: String,
+    /// Users who may edit and publish the topic besides the creator
+    #[serde(default)]
+    pub editors: Vec<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
 }
 
-#[server(GetTopics, "/api")]
-pub async fn get_topics() -> Result<Vec<Topic>, ServerFnError> {
-    let resp = reqwest::get(format!("{}/topic", base_url()))
+impl Topic {
+    /// Whether everyone may see the topic at `now`
+    pub fn is_public(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.draft {
+            return false;
+        }
+        match &self.publish_at {
+            // unparsable times are hidden rather than published early
+            Some(publish_at) => chrono::DateTime::parse_from_rfc3339(publish_at)
+                .map_or(false, |publish_at| publish_at <= now),
+            None => true,
+        }
+    }
+
+    /// Stage of the voting period at `now`, from the start and end times
+    ///
+    /// Falls back to the stage reported by the backend if the times cannot be parsed.
+    pub fn stage_at(&self, now: chrono::DateTime<chrono::Utc>) -> Option<Stage> {
+        let (Ok(starts_at), Ok(ends_at)) = (
+            chrono::DateTime::parse_from_rfc3339(&self.starts_at),
+            chrono::DateTime::parse_from_rfc3339(&self.ends_at),
+        ) else {
+            return self.stage.parse().ok();
+        };
+
+        let stage = if now < starts_at {
+            Stage::Upcoming
+        } else if now < ends_at {
+            Stage::Voting
+        } else {
+            Stage::Ended
+        };
+        Some(stage)
+    }
+
+    pub fn can_edit(&self, username: &str) -> bool {
+        self.creator == username || self.editors.iter().any(|e| e == username)
+    }
+
+    /// Whether `user` may see the topic at `now`, drafts and scheduled topics are only
+    /// shown to those who can edit them
+    pub fn is_visible_to(&self, user: Option<&User>, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.is_public(now) || user.is_some_and(|user| self.can_edit(&user.username))
+    }
+}
+
+/// Canonical form of a tag: lowercase, words joined by `-`, and only letters, digits,
+/// `-` and `_`, so that it can be put in a URL path as it is
+pub fn normalize_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Normalized `tags` without empty ones and duplicates, in the given order
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized = Vec::<String>::new();
+    for tag in tags.iter().map(|t| normalize_tag(t)) {
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    normalized
+}
+
+/// All topics everyone may see, in backend order
+#[cfg(feature = "ssr")]
+async fn all_topics() -> Result<Vec<Topic>, ServerFnError> {
+    // also the readiness probe, so report a down backend instead of panicking
+    let resp = client()
+        .get(format!("{}/topic", base_url()))
+        .send_traced()
         .await
-        .unwrap()
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(ServerFnError::ServerError(format!(
+            "get topics failed: {resp:?}"
+        )));
+    }
+
+    let mut topics = resp
         .json::<Vec<Topic>>()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+    // the backend hides drafts too, this only keeps scheduled topics back
+    let now = chrono::Utc::now();
+    topics.retain(|topic| topic.is_public(now));
+
+    Ok(topics)
+}
+
+/// Stage of a topic in its voting period
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Upcoming,
+    Voting,
+    Ended,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 3] = [Stage::Upcoming, Stage::Voting, Stage::Ended];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Upcoming => "upcoming",
+            Stage::Voting => "voting",
+            Stage::Ended => "ended",
+        }
+    }
+
+    /// Id of the label in the catalogs under `locales/`
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            Stage::Upcoming => "stage-upcoming",
+            Stage::Voting => "stage-voting",
+            Stage::Ended => "stage-ended",
+        }
+    }
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Stage::ALL
+            .into_iter()
+            .find(|stage| stage.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown stage {s:?}"))
+    }
+}
+
+/// Order of a topic list
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TopicSort {
+    /// Newest first
+    #[default]
+    Created,
+    /// Closest end date first
+    Ends,
+    /// Most votes first, from counts up to a minute old
+    Participation,
+}
+
+impl TopicSort {
+    pub const ALL: [TopicSort; 3] = [
+        TopicSort::Created,
+        TopicSort::Ends,
+        TopicSort::Participation,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TopicSort::Created => "created",
+            TopicSort::Ends => "ends",
+            TopicSort::Participation => "participation",
+        }
+    }
+
+    /// Id of the label in the catalogs under `locales/`
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            TopicSort::Created => "sort-created",
+            TopicSort::Ends => "sort-ends",
+            TopicSort::Participation => "sort-participation",
+        }
+    }
+}
+
+impl FromStr for TopicSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TopicSort::ALL
+            .into_iter()
+            .find(|sort| sort.as_str() == s)
+            .ok_or_else(|| format!("unknown sort {s:?}"))
+    }
+}
+
+/// Search, stage filter and order of a topic list
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct TopicQuery {
+    /// Words that must all appear in the description or an option label
+    pub search: String,
+    /// Only topics in one of these stages, any stage if empty
+    pub stages: Vec<Stage>,
+    /// Only topics with this tag
+    pub tag: Option<String>,
+    pub sort: TopicSort,
+}
+
+impl TopicQuery {
+    /// Whether `topic` is listed, its stage is the one at `now` as on its badge
+    pub fn matches(&self, topic: &Topic, now: chrono::DateTime<chrono::Utc>) -> bool {
+        let in_stage = self.stages.is_empty()
+            || topic
+                .stage_at(now)
+                .is_some_and(|stage| self.stages.contains(&stage));
+        let text = std::iter::once(&topic.description)
+            .chain(topic.options.iter().map(|opt| &opt.label))
+            .map(|s| s.to_lowercase())
+            .collect::<Vec<_>>();
+        let found = self
+            .search
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| text.iter().any(|s| s.contains(word)));
+
+        let tagged = self.tag.as_ref().map_or(true, |tag| {
+            let tag = normalize_tag(tag);
+            topic.tags.iter().any(|t| normalize_tag(t) == tag)
+        });
+
+        in_stage && found && tagged
+    }
+}
+
+/// How long a topic's vote count is reused for [`TopicSort::Participation`]
+#[cfg(feature = "ssr")]
+const VOTE_COUNT_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Number of votes per topic, `None` if not known
+///
+/// Only counts that [`get_vote_result`] would show anyone are used, and they are cached
+/// so that sorting does not ask the backend for every topic on every request. Upcoming
+/// topics have no votes, so they are not looked up at all.
+#[cfg(feature = "ssr")]
+async fn vote_counts(topics: &[Topic]) -> Vec<Option<usize>> {
+    use futures::{stream, StreamExt};
+    use std::{
+        sync::{Mutex, OnceLock},
+        time::Instant,
+    };
+
+    static CACHE: OnceLock<Mutex<HashMap<String, (Instant, usize)>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    let now = chrono::Utc::now();
+    cache
+        .lock()
+        .unwrap()
+        .retain(|_, (fetched_at, _)| fetched_at.elapsed() < VOTE_COUNT_TTL);
+
+    stream::iter(topics)
+        .map(|topic| async move {
+            if topic.stage_at(now) == Some(Stage::Upcoming) {
+                return None;
+            }
+            if let Some((_, count)) = cache.lock().unwrap().get(&topic.id) {
+                return Some(*count);
+            }
+            // only public topics are listed
+            let count = vote_result(&topic.id).await.ok()?.values().sum();
+            cache
+                .lock()
+                .unwrap()
+                .insert(topic.id.clone(), (Instant::now(), count));
+            Some(count)
+        })
+        .buffered(8)
+        .collect()
+        .await
+}
+
+/// Largest page [`get_topics`] returns
+pub const MAX_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct GetTopicsInput {
+    pub query: TopicQuery,
+    /// Not passed on to the backend, see [`get_topics`]
+    pub page: PageInput,
+}
+
+/// One page of the topics matching `input.query`
+///
+/// The backend's `GET /topic` has no search, sort or paging parameters, so every call
+/// downloads all topics and they are filtered, sorted and paged here. Fine for the
+/// few hundred topics of a department; the backend needs paging before this grows.
+#[server(GetTopics, "/api")]
+pub async fn get_topics(input: GetTopicsInput) -> Result<Paginated<Topic>, ServerFnError> {
+    let GetTopicsInput { query, page } = input;
+    let mut topics = all_topics().await?;
+    let now = chrono::Utc::now();
+    topics.retain(|topic| query.matches(topic, now));
 
-    Ok(resp)
+    // timestamps are ISO 8601 in UTC, so they sort as strings
+    match query.sort {
+        TopicSort::Created => topics.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+        TopicSort::Ends => topics.sort_by(|a, b| a.ends_at.cmp(&b.ends_at)),
+        TopicSort::Participation => {
+            let counts = vote_counts(&topics).await;
+            let mut ranked = counts.into_iter().zip(topics).collect::<Vec<_>>();
+            // topics without a known count last
+            ranked.sort_by(|a, b| b.0.cmp(&a.0));
+            topics = ranked.into_iter().map(|(_, topic)| topic).collect();
+        }
+    }
+
+    let page_size = page.page_size.clamp(1, MAX_PAGE_SIZE);
+    let page = page.page.max(1);
+    let total = topics.len();
+    let items = topics
+        .into_iter()
+        .skip((page - 1) * page_size)
+        .take(page_size)
+        .collect();
+
+    Ok(Paginated {
+        items,
+        total,
+        page,
+        page_size,
+    })
 }
 
+/// A topic, or not found if `token` does not belong to one of its editors while it is a
+/// draft or scheduled
 #[server(GetOneTopic, "/api")]
-pub async fn get_one_topic(id: String) -> Result<Topic, ServerFnError> {
-    let resp = reqwest::get(format!("{}/topic/{id}", base_url()))
+pub async fn get_one_topic(id: String, token: Option<String>) -> Result<Topic, ServerFnError> {
+    let mut req = client().get(format!("{}/topic/{id}", base_url()));
+    if let Some(token) = &token {
+        req = req.bearer_auth(token);
+    }
+    let resp = req
+        .send_traced()
         .await
-        .unwrap()
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get topic failed: {resp:?}"
+        )));
+    }
+
+    let topic = resp
         .json::<Topic>()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    let now = chrono::Utc::now();
+    if !topic.is_public(now) {
+        let user = match token {
+            Some(token) => get_me(token).await.ok(),
+            None => None,
+        };
+        if !topic.is_visible_to(user.as_ref(), now) {
+            return Err(ServerFnError::ServerError("topic not found".to_string()));
+        }
+    }
 
-    Ok(resp)
+    Ok(topic)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CreateOptionInput {
     pub label: String,
     pub description: String,
+    #[serde(default)]
+    pub image: Option<Image>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -69,17 +615,36 @@ pub struct CreateTopicInput {
     pub starts_at: String,
     pub ends_at: String,
     pub options: Vec<CreateOptionInput>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Save without publishing
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub publish_at: Option<String>,
+    #[serde(default)]
+    pub editors: Vec<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
 }
 
 #[server(CreateTopic, "/api")]
-pub async fn create_topic(input: CreateTopicInput) -> Result<(), ServerFnError> {
-    let client = reqwest::Client::new();
+pub async fn create_topic(token: String, input: CreateTopicInput) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Organizer).await?;
+    let input = CreateTopicInput {
+        tags: normalize_tags(&input.tags),
+        ..input
+    };
+    claim_uploads(&input.attachments, &input.options).await?;
+
+    let client = client();
     let resp = client
         .post(format!("{}/topic", base_url()))
+        .bearer_auth(token)
         .json(&input)
-        .send()
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
 
     if resp.status() != reqwest::StatusCode::OK {
         return Err(ServerFnError::ServerError(format!(
@@ -90,148 +655,177 @@ pub async fn create_topic(input: CreateTopicInput) -> Result<(), ServerFnError>
     Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CreateVoteInput {
-    pub topic_id: String,
-    pub option_id: String,
-}
-
-#[server(CreateVote, "/api")]
-pub async fn create_vote(token: String, input: CreateVoteInput) -> Result<(), ServerFnError> {
-    let client = reqwest::Client::new();
+/// Drafts and scheduled topics the user created or co-edits
+#[server(GetDrafts, "/api")]
+pub async fn get_drafts(token: String) -> Result<Vec<Topic>, ServerFnError> {
+    let client = client();
     let resp = client
-        .post(format!("{}/vote", base_url()))
+        .get(format!("{}/me/drafts", base_url()))
         .bearer_auth(token)
-        .json(&input)
-        .send()
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get drafts failed: {resp:?}"
+        )));
+    }
+    let topics = resp
+        .json::<Vec<Topic>>()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
 
-    Ok(())
+    Ok(topics)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Vote {
-    pub id: String,
-    pub username: String,
+pub struct UpdateTopicInput {
     pub topic_id: String,
-    pub option_id: String,
+    pub topic: CreateTopicInput,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GetMyVoteInput {
-    pub topic_id: String,
-}
+/// Replace a draft or scheduled topic, only its creator and editors may
+#[server(UpdateTopic, "/api")]
+pub async fn update_topic(token: String, input: UpdateTopicInput) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Organizer).await?;
+    let topic = CreateTopicInput {
+        tags: normalize_tags(&input.topic.tags),
+        ..input.topic
+    };
+    claim_uploads(&topic.attachments, &topic.options).await?;
 
-#[server(GetMyVote, "/api")]
-pub async fn get_my_vote(token: String, input: GetMyVoteInput) -> Result<Vote, ServerFnError> {
-    let client = reqwest::Client::new();
+    let client = client();
     let resp = client
-        .get(format!("{}/topic/{}/my-vote", base_url(), input.topic_id))
+        .put(format!("{}/topic/{}", base_url(), input.topic_id))
         .bearer_auth(token)
-        .send()
+        .json(&topic)
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
 
     if resp.status() != reqwest::StatusCode::OK {
         return Err(ServerFnError::ServerError(format!(
-            "get my vote failed: {resp:?}"
+            "update topic failed: {resp:?}"
         )));
     }
 
-    let vote = resp
-        .json::<Vote>()
-        .await
-        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
-
-    Ok(vote)
+    Ok(())
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GetVoteResultInput {
+pub struct PublishTopicInput {
     pub topic_id: String,
+    /// Publish at this time instead of right away
+    pub publish_at: Option<String>,
 }
 
-#[server(GetVoteResult, "/api")]
-pub async fn get_vote_result(
-    input: GetVoteResultInput,
-) -> Result<HashMap<String, usize>, ServerFnError> {
-    let client = reqwest::Client::new();
+#[server(PublishTopic, "/api")]
+pub async fn publish_topic(token: String, input: PublishTopicInput) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Organizer).await?;
+
+    let client = client();
     let resp = client
-        .get(format!(
-            "{}/topic/{}/vote-result",
-            base_url(),
-            input.topic_id
-        ))
-        .send()
+        .post(format!("{}/topic/{}/publish", base_url(), input.topic_id))
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
 
     if resp.status() != reqwest::StatusCode::OK {
         return Err(ServerFnError::ServerError(format!(
-            "get vote result failed: {resp:?}"
+            "publish topic failed: {resp:?}"
         )));
     }
 
-    let vote = resp
-        .json::<HashMap<String, usize>>()
-        .await
-        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
-
-    Ok(vote)
+    Ok(())
 }
 
+/// A named set of options and a schedule to start new topics from
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct OAuth2PasswordRequest {
-    pub username: String,
-    pub password: String,
+pub struct TopicTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub options: Vec<CreateOptionInput>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Filled in when the template is used, picking a new start moves the end along; empty
+    /// if not set
+    #[serde(default)]
+    pub starts_at: String,
+    #[serde(default)]
+    pub ends_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Token {
-    pub access_token: String,
-    pub token_type: String,
+pub struct CreateTemplateInput {
+    pub name: String,
+    pub description: String,
+    pub options: Vec<CreateOptionInput>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub starts_at: String,
+    #[serde(default)]
+    pub ends_at: String,
 }
 
-#[server(CreateAccessToken, "/api")]
-pub async fn create_access_token(input: OAuth2PasswordRequest) -> Result<Token, ServerFnError> {
-    let client = reqwest::Client::new();
+/// Templates shared by all organizers
+#[server(GetTemplates, "/api")]
+pub async fn get_templates(token: String) -> Result<Vec<TopicTemplate>, ServerFnError> {
+    require_role(&token, Role::Organizer).await?;
+
+    let client = client();
     let resp = client
-        .post(format!("{}/auth/token", base_url()))
-        .form(&input)
-        .send()
+        .get(format!("{}/template", base_url()))
+        .bearer_auth(token)
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
     if resp.status() != reqwest::StatusCode::OK {
         return Err(ServerFnError::ServerError(format!(
-            "login failed: {resp:?}"
+            "get templates failed: {resp:?}"
         )));
     }
+    let templates = resp
+        .json::<Vec<TopicTemplate>>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
 
-    let token = resp.json::<Token>().await.unwrap();
-    Ok(token)
+    Ok(templates)
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SignupInput {
-    pub username: String,
-    pub email: String,
-    pub password: String,
-}
+#[server(CreateTemplate, "/api")]
+pub async fn create_template(
+    token: String,
+    input: CreateTemplateInput,
+) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Organizer).await?;
+    let input = CreateTemplateInput {
+        name: input.name.trim().to_string(),
+        tags: normalize_tags(&input.tags),
+        ..input
+    };
+    if input.name.is_empty() {
+        return Err(ServerFnError::ServerError(
+            "a template needs a name".to_string(),
+        ));
+    }
+    claim_uploads(&[], &input.options).await?;
 
-#[server(Signup, "/api")]
-pub async fn signup(input: SignupInput) -> Result<(), ServerFnError> {
-    let client = reqwest::Client::new();
+    let client = client();
     let resp = client
-        .post(format!("{}/user/signup", base_url()))
+        .post(format!("{}/template", base_url()))
+        .bearer_auth(token)
         .json(&input)
-        .send()
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
 
     if resp.status() != reqwest::StatusCode::OK {
         return Err(ServerFnError::ServerError(format!(
-            "signup failed: {resp:?}"
+            "create template failed: {resp:?}"
         )));
     }
 
@@ -239,52 +833,902 @@ pub async fn signup(input: SignupInput) -> Result<(), ServerFnError> {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct User {
-    pub username: String,
+pub struct DeleteTemplateInput {
+    pub template_id: String,
 }
 
-#[server(GetMe, "/api")]
-pub async fn get_me(token: String) -> Result<User, ServerFnError> {
-    let client = reqwest::Client::new();
+#[server(DeleteTemplate, "/api")]
+pub async fn delete_template(
+    token: String,
+    input: DeleteTemplateInput,
+) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Organizer).await?;
+
+    let client = client();
     let resp = client
-        .get(format!("{}/me", base_url()))
+        .delete(format!("{}/template/{}", base_url(), input.template_id))
         .bearer_auth(token)
-        .send()
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
     if resp.status() != reqwest::StatusCode::OK {
-        return Err(ServerFnError::ServerError(format!("auth failed: {resp:?}")));
+        return Err(ServerFnError::ServerError(format!(
+            "delete template failed: {resp:?}"
+        )));
     }
-    let user = resp.json::<User>().await.unwrap();
 
-    Ok(user)
+    Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Comment {
-    pub id: String,
-    pub user_id: String,
-    pub content: String,
-    pub created_at: String,
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TagCount {
+    pub name: String,
+    /// Number of topics with this tag
+    pub topics: usize,
+}
+
+/// Every tag in use, most used first
+///
+/// Like topics, tags are counted here as the backend has no list of them.
+#[server(GetTags, "/api")]
+pub async fn get_tags() -> Result<Vec<TagCount>, ServerFnError> {
+    let mut counts = HashMap::<String, usize>::new();
+    for topic in all_topics().await? {
+        for tag in normalize_tags(&topic.tags) {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    let mut tags = counts
+        .into_iter()
+        .map(|(name, topics)| TagCount { name, topics })
+        .collect::<Vec<_>>();
+    tags.sort_by(|a, b| b.topics.cmp(&a.topics).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(tags)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GetCommentsInput {
-    pub topic_id: String,
+pub struct RenameTagInput {
+    pub tag: String,
+    pub new_name: String,
 }
 
-#[server(GetComments, "/api")]
-pub async fn get_comments(input: GetCommentsInput) -> Result<Vec<Comment>, ServerFnError> {
-    let client = reqwest::Client::new();
+/// Rename a tag on every topic
+#[server(RenameTag, "/api")]
+pub async fn rename_tag(token: String, input: RenameTagInput) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Admin).await?;
+    let input = RenameTagInput {
+        tag: normalize_tag(&input.tag),
+        new_name: normalize_tag(&input.new_name),
+    };
+    if input.tag.is_empty() || input.new_name.is_empty() {
+        return Err(ServerFnError::ServerError(
+            "tag names should not be empty".to_string(),
+        ));
+    }
+
+    let client = client();
     let resp = client
-        .get(format!("{}/comment", base_url()))
-        .query(&input)
-        .send()
+        .post(format!("{}/tag/{}/rename", base_url(), input.tag))
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
     if resp.status() != reqwest::StatusCode::OK {
         return Err(ServerFnError::ServerError(format!(
-            "get comments failed: {resp:?}"
+            "rename tag failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MergeTagsInput {
+    /// Tags to replace, they are gone afterwards
+    pub tags: Vec<String>,
+    pub into: String,
+}
+
+/// Replace `tags` with `into` on every topic
+#[server(MergeTags, "/api")]
+pub async fn merge_tags(token: String, input: MergeTagsInput) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Admin).await?;
+    let into = normalize_tag(&input.into);
+    let input = MergeTagsInput {
+        tags: normalize_tags(&input.tags)
+            .into_iter()
+            .filter(|tag| *tag != into)
+            .collect(),
+        into,
+    };
+    if input.tags.is_empty() || input.into.is_empty() {
+        return Err(ServerFnError::ServerError(
+            "pick the tags to merge and a tag to merge them into".to_string(),
+        ));
+    }
+
+    let client = client();
+    let resp = client
+        .post(format!("{}/tag/{}/merge", base_url(), input.into))
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "merge tags failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateVoteInput {
+    pub topic_id: String,
+    pub option_id: String,
+}
+
+#[server(CreateVote, "/api")]
+pub async fn create_vote(token: String, input: CreateVoteInput) -> Result<(), ServerFnError> {
+    let client = client();
+    let resp = client
+        .post(format!("{}/vote", base_url()))
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    let metrics = crate::server::metrics::metrics();
+    if !resp.status().is_success() {
+        metrics.vote_rejected(resp.status().as_u16());
+        return Err(ServerFnError::ServerError(format!(
+            "create vote failed: {resp:?}"
+        )));
+    }
+    metrics.vote_cast(&input.topic_id);
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Vote {
+    pub id: String,
+    pub username: String,
+    pub topic_id: String,
+    pub option_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetMyVoteInput {
+    pub topic_id: String,
+}
+
+#[server(GetMyVote, "/api")]
+pub async fn get_my_vote(token: String, input: GetMyVoteInput) -> Result<Vote, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/topic/{}/my-vote", base_url(), input.topic_id))
+        .bearer_auth(token)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get my vote failed: {resp:?}"
+        )));
+    }
+
+    let vote = resp
+        .json::<Vote>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(vote)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetVoteResultInput {
+    pub topic_id: String,
+}
+
+/// Votes per option of a topic the user may see, see [`get_one_topic`]
+#[server(GetVoteResult, "/api")]
+pub async fn get_vote_result(
+    token: Option<String>,
+    input: GetVoteResultInput,
+) -> Result<HashMap<String, usize>, ServerFnError> {
+    get_one_topic(input.topic_id.clone(), token).await?;
+
+    vote_result(&input.topic_id).await
+}
+
+/// Votes per option of any topic, callers check that it may be seen
+#[cfg(feature = "ssr")]
+async fn vote_result(topic_id: &str) -> Result<HashMap<String, usize>, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/topic/{topic_id}/vote-result", base_url()))
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get vote result failed: {resp:?}"
+        )));
+    }
+
+    let vote = resp
+        .json::<HashMap<String, usize>>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(vote)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuth2PasswordRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Token {
+    pub access_token: String,
+    pub token_type: String,
+}
+
+#[server(CreateAccessToken, "/api")]
+pub async fn create_access_token(input: OAuth2PasswordRequest) -> Result<Token, ServerFnError> {
+    let client = client();
+    let resp = client
+        .post(format!("{}/auth/token", base_url()))
+        .form(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "login failed: {resp:?}"
+        )));
+    }
+
+    let token = resp
+        .json::<Token>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+    Ok(token)
+}
+
+/// Whether "Login with school account" (OpenID Connect) is configured on the server
+#[server(SsoEnabled, "/api")]
+pub async fn sso_enabled(cx: Scope) -> Result<bool, ServerFnError> {
+    use actix_web::{web::Data, HttpRequest};
+
+    let req = use_context::<HttpRequest>(cx)
+        .ok_or_else(|| ServerFnError::ServerError("missing request".to_string()))?;
+
+    Ok(req.app_data::<Data<crate::server::oidc::Oidc>>().is_some())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetCooldownInput {
+    /// Name of the rate limited server fn, e.g. `create_access_token`
+    pub server_fn: String,
+    /// Token or username the limited call was made with
+    pub user: Option<String>,
+}
+
+/// Seconds until the caller may call a rate limited server fn again
+///
+/// Rejected calls are answered with 429, which the client can't decode, so pages
+/// ask here after a failure.
+#[server(GetCooldown, "/api")]
+pub async fn get_cooldown(cx: Scope, input: GetCooldownInput) -> Result<u64, ServerFnError> {
+    use crate::server::rate_limit::{clients, ip_client, RateLimiter, User};
+    use actix_web::{web::Data, HttpRequest};
+
+    let req = use_context::<HttpRequest>(cx)
+        .ok_or_else(|| ServerFnError::ServerError("missing request".to_string()))?;
+    let Some(limiter) = req.app_data::<Data<RateLimiter>>() else {
+        return Ok(0);
+    };
+    // whether `user` is a token or a username is not known here, buckets that don't
+    // exist don't add to the cooldown
+    let ip = ip_client(&req);
+    let clients = match input.user {
+        Some(user) => [
+            limiter.resolve(User::Token(user.clone())).await,
+            User::Username(user),
+        ]
+        .iter()
+        .flat_map(|user| clients(ip.clone(), Some(user)))
+        .collect(),
+        None => clients(ip, None),
+    };
+
+    Ok(limiter
+        .cooldown(&input.server_fn, &clients)
+        .as_secs_f64()
+        .ceil() as u64)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SignupInput {
+    pub username: String,
+    pub email: String,
+    pub password: String,
+}
+
+/// Field-level problems with a submitted form, keyed by field name
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct FieldErrors(pub HashMap<String, String>);
+
+/// Marks a [`ServerFnError::ServerError`] carrying [`FieldErrors`] as JSON
+const FIELD_ERRORS_PREFIX: &str = "field errors: ";
+
+impl From<FieldErrors> for ServerFnError {
+    fn from(errors: FieldErrors) -> Self {
+        let json = serde_json::to_string(&errors.0).unwrap_or_default();
+        ServerFnError::ServerError(format!("{FIELD_ERRORS_PREFIX}{json}"))
+    }
+}
+
+impl FieldErrors {
+    /// The field errors a server fn failed with, `None` for any other error
+    pub fn from_error(error: &ServerFnError) -> Option<Self> {
+        let ServerFnError::ServerError(message) = error else {
+            return None;
+        };
+        serde_json::from_str(message.strip_prefix(FIELD_ERRORS_PREFIX)?)
+            .ok()
+            .map(Self)
+    }
+
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, field: &str, message: String) {
+        self.0.insert(field.to_string(), message);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Map a backend `detail` body to fields, it is either a message
+    /// or a list of `{ "loc": [.., field], "msg": .. }` validation errors
+    #[cfg(feature = "ssr")]
+    fn from_detail(detail: &serde_json::Value, fields: &[&str]) -> Self {
+        let mut errors = Self::default();
+        match detail {
+            serde_json::Value::String(msg) => {
+                let lower = msg.to_lowercase();
+                let field = fields
+                    .iter()
+                    .find(|f| lower.contains(*f))
+                    .copied()
+                    .unwrap_or("form");
+                errors.insert(field, msg.clone());
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    let field = item["loc"]
+                        .as_array()
+                        .and_then(|loc| loc.last())
+                        .and_then(|f| f.as_str())
+                        .filter(|f| fields.contains(f))
+                        .unwrap_or("form");
+                    let msg = item["msg"].as_str().unwrap_or("invalid value");
+                    errors.insert(field, msg.to_string());
+                }
+            }
+            _ => errors.insert("form", detail.to_string()),
+        }
+        errors
+    }
+}
+
+#[server(Signup, "/api")]
+pub async fn signup(input: SignupInput) -> Result<(), ServerFnError> {
+    use crate::validate::{validate_email, validate_password, validate_username};
+
+    let mut errors = FieldErrors::default();
+    if !crate::server::config::config().features.signup {
+        errors.insert("form", "signup-disabled".to_string());
+        return Err(errors.into());
+    }
+    if let Err(invalid) = validate_username(&input.username) {
+        errors.insert("username", invalid.message_id().to_string());
+    }
+    if let Err(invalid) = validate_email(&input.email) {
+        errors.insert("email", invalid.message_id().to_string());
+    }
+    if let Err(invalid) = validate_password(&input.password) {
+        errors.insert("password", invalid.message_id().to_string());
+    }
+    if !errors.is_empty() {
+        return Err(errors.into());
+    }
+
+    let client = client();
+    let resp = client
+        .post(format!("{}/user/signup", base_url()))
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status().is_client_error() {
+        #[derive(Deserialize)]
+        struct Detail {
+            detail: serde_json::Value,
+        }
+
+        let detail = resp
+            .json::<Detail>()
+            .await
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+        let fields = ["username", "email", "password"];
+        return Err(FieldErrors::from_detail(&detail.detail, &fields).into());
+    }
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "signup failed: {resp:?}"
+        )));
+    }
+
+    // the account exists now, a failed mail can be resent from the verify email page
+    if let Err(e) = send_verification_mail(input.email).await {
+        tracing::warn!("send verification mail failed: {e}");
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CheckAvailabilityInput {
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct Availability {
+    #[serde(default)]
+    pub username_taken: bool,
+    #[serde(default)]
+    pub email_taken: bool,
+}
+
+#[server(CheckAvailability, "/api")]
+pub async fn check_availability(
+    input: CheckAvailabilityInput,
+) -> Result<Availability, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/user/availability", base_url()))
+        .query(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "check availability failed: {resp:?}"
+        )));
+    }
+
+    let availability = resp
+        .json::<Availability>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(availability)
+}
+
+/// A one-time token issued by the backend, e.g. for password reset or email verification
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ticket {
+    pub token: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmailInput {
+    pub email: String,
+}
+
+/// Ask the backend for an email verification ticket and mail the link to `email`
+#[cfg(feature = "ssr")]
+async fn send_verification_mail(email: String) -> Result<(), ServerFnError> {
+    use crate::server::{
+        config::public_url,
+        mail::{mailer, Mail},
+    };
+
+    let client = client();
+    let resp = client
+        .post(format!("{}/auth/email-verification", base_url()))
+        .json(&EmailInput {
+            email: email.clone(),
+        })
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    // answer like a sent mail for unknown or already verified emails, so that this can't
+    // be used to find out who has an account
+    if matches!(
+        resp.status(),
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::CONFLICT
+    ) {
+        return Ok(());
+    }
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "request email verification failed: {resp:?}"
+        )));
+    }
+    let ticket = resp
+        .json::<Ticket>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    let mail = Mail {
+        to: email,
+        subject: "Verify your email".to_string(),
+        body: format!(
+            "Open the link below to verify your email:\n\n{}/verify-email?token={}\n\nThe link expires at {}.",
+            public_url(),
+            ticket.token,
+            ticket.expires_at
+        ),
+    };
+    mailer()
+        .send(mail)
+        .await
+        .map_err(|e| ServerFnError::ServerError(format!("send mail failed: {e}")))?;
+
+    Ok(())
+}
+
+#[server(RequestEmailVerification, "/api")]
+pub async fn request_email_verification(input: EmailInput) -> Result<(), ServerFnError> {
+    send_verification_mail(input.email).await
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerifyEmailInput {
+    pub token: String,
+}
+
+#[server(VerifyEmail, "/api")]
+pub async fn verify_email(input: VerifyEmailInput) -> Result<(), ServerFnError> {
+    let client = client();
+    let resp = client
+        .post(format!("{}/auth/email-verification/confirm", base_url()))
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "verify email failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[server(RequestPasswordReset, "/api")]
+pub async fn request_password_reset(input: EmailInput) -> Result<(), ServerFnError> {
+    use crate::server::{
+        config::public_url,
+        mail::{mailer, Mail},
+    };
+
+    let client = client();
+    let resp = client
+        .post(format!("{}/auth/password-reset", base_url()))
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    // do not reveal whether the email belongs to an account
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(());
+    }
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "request password reset failed: {resp:?}"
+        )));
+    }
+    let ticket = resp
+        .json::<Ticket>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    let mail = Mail {
+        to: input.email,
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Open the link below to choose a new password:\n\n{}/reset-password?token={}\n\nThe link expires at {}. If you did not ask for this, ignore this mail.",
+            public_url(),
+            ticket.token,
+            ticket.expires_at
+        ),
+    };
+    mailer()
+        .send(mail)
+        .await
+        .map_err(|e| ServerFnError::ServerError(format!("send mail failed: {e}")))?;
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResetPasswordInput {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[server(ResetPassword, "/api")]
+pub async fn reset_password(input: ResetPasswordInput) -> Result<(), ServerFnError> {
+    crate::validate::validate_password(&input.new_password)
+        .map_err(|invalid| ServerFnError::ServerError(invalid.message_id().to_string()))?;
+
+    let client = client();
+    let resp = client
+        .post(format!("{}/auth/password-reset/confirm", base_url()))
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "reset password failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangePasswordInput {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[server(ChangePassword, "/api")]
+pub async fn change_password(
+    token: String,
+    input: ChangePasswordInput,
+) -> Result<(), ServerFnError> {
+    crate::validate::validate_password(&input.new_password)
+        .map_err(|invalid| ServerFnError::ServerError(invalid.message_id().to_string()))?;
+
+    let client = client();
+    let resp = client
+        .post(format!("{}/me/password", base_url()))
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "change password failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Can vote and comment
+    #[default]
+    Voter,
+    /// Can also create topics
+    Organizer,
+    /// Can also moderate comments
+    Admin,
+}
+
+impl Role {
+    /// Whether this role grants at least the permissions of `required`
+    pub fn allows(self, required: Role) -> bool {
+        self >= required
+    }
+
+    /// Id of the name in the catalogs under `locales/`
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            Role::Voter => "role-voter",
+            Role::Organizer => "role-organizer",
+            Role::Admin => "role-admin",
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Role::Voter => "voter",
+            Role::Organizer => "organizer",
+            Role::Admin => "admin",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct User {
+    pub username: String,
+    #[serde(default)]
+    pub role: Role,
+}
+
+#[server(GetMe, "/api")]
+pub async fn get_me(token: String) -> Result<User, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/me", base_url()))
+        .bearer_auth(token)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!("auth failed: {resp:?}")));
+    }
+    let user = resp
+        .json::<User>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(user)
+}
+
+/// Fetch the owner of `token` and make sure they have at least the `required` role
+#[cfg(feature = "ssr")]
+pub(crate) async fn require_role(token: &str, required: Role) -> Result<User, ServerFnError> {
+    let user = get_me(token.to_string()).await?;
+    if !user.role.allows(required) {
+        return Err(ServerFnError::ServerError(format!(
+            "permission denied: {required} role required"
+        )));
+    }
+
+    Ok(user)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Comment {
+    pub id: String,
+    #[serde(default)]
+    pub topic_id: String,
+    pub user_id: String,
+    pub content: String,
+    pub created_at: String,
+}
+
+#[server(GetMyTopics, "/api")]
+pub async fn get_my_topics(
+    token: String,
+    input: PageInput,
+) -> Result<Paginated<Topic>, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/me/topics", base_url()))
+        .bearer_auth(token)
+        .query(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get my topics failed: {resp:?}"
+        )));
+    }
+    let topics = resp
+        .json::<Paginated<Topic>>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(topics)
+}
+
+/// A topic the user has voted on
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MyVote {
+    pub topic: Topic,
+    /// The chosen option, `None` if the topic hides ballots (anonymous voting)
+    pub option_id: Option<String>,
+}
+
+#[server(GetMyVotes, "/api")]
+pub async fn get_my_votes(
+    token: String,
+    input: PageInput,
+) -> Result<Paginated<MyVote>, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/me/votes", base_url()))
+        .bearer_auth(token)
+        .query(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get my votes failed: {resp:?}"
+        )));
+    }
+    let votes = resp
+        .json::<Paginated<MyVote>>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(votes)
+}
+
+#[server(GetMyComments, "/api")]
+pub async fn get_my_comments(
+    token: String,
+    input: PageInput,
+) -> Result<Paginated<Comment>, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/me/comments", base_url()))
+        .bearer_auth(token)
+        .query(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get my comments failed: {resp:?}"
+        )));
+    }
+    let comments = resp
+        .json::<Paginated<Comment>>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(comments)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetCommentsInput {
+    pub topic_id: String,
+}
+
+#[server(GetComments, "/api")]
+pub async fn get_comments(input: GetCommentsInput) -> Result<Vec<Comment>, ServerFnError> {
+    let client = client();
+    let resp = client
+        .get(format!("{}/comment", base_url()))
+        .query(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get comments failed: {resp:?}"
         )));
     }
     let comments = resp
@@ -303,14 +1747,14 @@ pub struct CreateCommentInput {
 
 #[server(CreateComment, "/api")]
 pub async fn create_comment(token: String, input: CreateCommentInput) -> Result<(), ServerFnError> {
-    let client = reqwest::Client::new();
+    let client = client();
     let resp = client
         .post(format!("{}/comment", base_url()))
         .bearer_auth(token)
         .json(&input)
-        .send()
+        .send_traced()
         .await
-        .unwrap();
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
 
     if resp.status() != reqwest::StatusCode::OK {
         return Err(ServerFnError::ServerError(format!(
@@ -320,3 +1764,456 @@ pub async fn create_comment(token: String, input: CreateCommentInput) -> Result<
 
     Ok(())
 }
+
+/// Emojis that can be used to react to a comment
+pub const REACTION_EMOJIS: [&str; 4] = ["👍", "👎", "❤️", "🤔"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Reaction {
+    pub emoji: String,
+    pub count: usize,
+    /// Whether the current user has reacted with this emoji
+    #[serde(default)]
+    pub reacted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetReactionsInput {
+    pub comment_id: String,
+}
+
+#[server(GetReactions, "/api")]
+pub async fn get_reactions(
+    token: Option<String>,
+    input: GetReactionsInput,
+) -> Result<Vec<Reaction>, ServerFnError> {
+    let client = client();
+    let mut req = client.get(format!(
+        "{}/comment/{}/reaction",
+        base_url(),
+        input.comment_id
+    ));
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+    let resp = req
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get reactions failed: {resp:?}"
+        )));
+    }
+
+    let reactions = resp
+        .json::<Vec<Reaction>>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(reactions)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetReactionInput {
+    pub comment_id: String,
+    pub emoji: String,
+    /// `true` to add the reaction, `false` to withdraw it
+    pub reacted: bool,
+}
+
+#[server(SetReaction, "/api")]
+pub async fn set_reaction(token: String, input: SetReactionInput) -> Result<(), ServerFnError> {
+    if !REACTION_EMOJIS.contains(&input.emoji.as_str()) {
+        return Err(ServerFnError::ServerError(
+            "comment-reaction-unknown".to_string(),
+        ));
+    }
+
+    let client = client();
+    let url = format!("{}/comment/{}/reaction", base_url(), input.comment_id);
+    let req = if input.reacted {
+        client.post(url)
+    } else {
+        client.delete(url)
+    };
+    let resp = req
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "set reaction failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportCommentInput {
+    pub comment_id: String,
+    pub reason: String,
+}
+
+#[server(ReportComment, "/api")]
+pub async fn report_comment(token: String, input: ReportCommentInput) -> Result<(), ServerFnError> {
+    let input = ReportCommentInput {
+        reason: input.reason.trim().to_string(),
+        ..input
+    };
+    if input.reason.is_empty() {
+        return Err(ServerFnError::ServerError(
+            "comment-report-reason-required".to_string(),
+        ));
+    }
+
+    let client = client();
+    let resp = client
+        .post(format!(
+            "{}/comment/{}/report",
+            base_url(),
+            input.comment_id
+        ))
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "report comment failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Report {
+    pub id: String,
+    pub comment: Comment,
+    pub reporter: String,
+    pub reason: String,
+    pub created_at: String,
+}
+
+#[server(GetReports, "/api")]
+pub async fn get_reports(token: String) -> Result<Vec<Report>, ServerFnError> {
+    require_role(&token, Role::Admin).await?;
+
+    let client = client();
+    let resp = client
+        .get(format!("{}/report", base_url()))
+        .bearer_auth(token)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "get reports failed: {resp:?}"
+        )));
+    }
+
+    let reports = resp
+        .json::<Vec<Report>>()
+        .await
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+
+    Ok(reports)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportAction {
+    /// Dismiss the report and keep the comment
+    Keep,
+    /// Remove the reported comment
+    Remove,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResolveReportInput {
+    pub report_id: String,
+    pub action: ReportAction,
+}
+
+#[server(ResolveReport, "/api")]
+pub async fn resolve_report(token: String, input: ResolveReportInput) -> Result<(), ServerFnError> {
+    require_role(&token, Role::Admin).await?;
+
+    let client = client();
+    let resp = client
+        .post(format!("{}/report/{}/resolve", base_url(), input.report_id))
+        .bearer_auth(token)
+        .json(&input)
+        .send_traced()
+        .await
+        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+    if resp.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::ServerError(format!(
+            "resolve report failed: {resp:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_errors_survive_the_server_fn_error() {
+        let mut errors = FieldErrors::default();
+        errors.insert("username", "validate-username-chars".to_string());
+        let error = ServerFnError::from(errors.clone());
+
+        assert_eq!(FieldErrors::from_error(&error), Some(errors));
+        assert_eq!(
+            FieldErrors::from_error(&ServerFnError::ServerError("signup failed".to_string())),
+            None
+        );
+    }
+
+    #[cfg(feature = "ssr")]
+    #[actix_web::test]
+    async fn reactions_and_reports_are_checked_before_the_backend() {
+        let reaction = |emoji: &str| SetReactionInput {
+            comment_id: "1".to_string(),
+            emoji: emoji.to_string(),
+            reacted: true,
+        };
+        let error = set_reaction("t".to_string(), reaction("💩"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, ServerFnError::ServerError(id) if id == "comment-reaction-unknown"),
+            "{error}"
+        );
+
+        for reason in ["", " \n\t "] {
+            let input = ReportCommentInput {
+                comment_id: "1".to_string(),
+                reason: reason.to_string(),
+            };
+            let error = report_comment("t".to_string(), input).await.unwrap_err();
+            assert!(
+                matches!(&error, ServerFnError::ServerError(id) if id == "comment-report-reason-required"),
+                "{error}"
+            );
+        }
+    }
+
+    fn topic(description: &str, stage: &str, tags: &[&str]) -> Topic {
+        Topic {
+            id: "1".to_string(),
+            description: description.to_string(),
+            starts_at: "2024-01-01T00:00:00Z".to_string(),
+            ends_at: "2024-01-08T00:00:00Z".to_string(),
+            created_at: "2023-12-01T00:00:00Z".to_string(),
+            updated_at: "2023-12-01T00:00:00Z".to_string(),
+            options: vec![VoteOption {
+                id: "a".to_string(),
+                label: "Night Market".to_string(),
+                description: String::new(),
+                image: None,
+            }],
+            stage: stage.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            draft: false,
+            publish_at: None,
+            creator: "alice".to_string(),
+            editors: vec![],
+            attachments: vec![],
+        }
+    }
+
+    #[test]
+    fn query_matches_all_words_in_description_or_options() {
+        let topic = topic("Where should the class trip go?", "voting", &[]);
+        let query = |search: &str| TopicQuery {
+            search: search.to_string(),
+            ..Default::default()
+        };
+
+        let now = chrono::Utc::now();
+        assert!(query("").matches(&topic, now));
+        assert!(query("CLASS trip").matches(&topic, now));
+        assert!(query("trip market").matches(&topic, now));
+        assert!(!query("trip beach").matches(&topic, now));
+    }
+
+    #[test]
+    fn query_matches_stages_and_tags() {
+        let topic = topic("Budget", "Voting", &["Student Council"]);
+
+        let stages = |stages: Vec<Stage>| TopicQuery {
+            stages,
+            ..Default::default()
+        };
+        let voting = "2024-01-05T00:00:00Z".parse().unwrap();
+        assert!(stages(vec![]).matches(&topic, voting));
+        assert!(stages(vec![Stage::Ended, Stage::Voting]).matches(&topic, voting));
+        assert!(!stages(vec![Stage::Upcoming]).matches(&topic, voting));
+        // the backend has not noticed the end yet, the badge has
+        let ended = "2024-01-08T00:00:01Z".parse().unwrap();
+        assert!(stages(vec![Stage::Ended]).matches(&topic, ended));
+        assert!(!stages(vec![Stage::Voting]).matches(&topic, ended));
+
+        let tag = |tag: &str| TopicQuery {
+            tag: Some(tag.to_string()),
+            ..Default::default()
+        };
+        assert!(tag("student-council").matches(&topic, voting));
+        assert!(tag("Student  Council").matches(&topic, voting));
+        assert!(!tag("student").matches(&topic, voting));
+    }
+
+    #[test]
+    fn normalize_tag_to_a_path_segment() {
+        assert_eq!(normalize_tag("  Student   Council "), "student-council");
+        assert_eq!(normalize_tag("C++ / Rust!"), "c-rust");
+        assert_eq!(normalize_tag("../admin"), "admin");
+        assert_eq!(normalize_tag("snake_case-Tag"), "snake_case-tag");
+        assert_eq!(normalize_tag("ÄRA 投票"), "ära-投票");
+        assert_eq!(normalize_tag(" ?#% "), "");
+    }
+
+    #[test]
+    fn normalize_tags_drops_empty_and_duplicates() {
+        let tags = ["Rust", "", "rust", "  ", "Web Dev", "web-dev", "go"].map(String::from);
+        assert_eq!(normalize_tags(&tags), ["rust", "web-dev", "go"]);
+    }
+
+    #[test]
+    fn scheduled_topics_are_only_visible_to_their_editors() {
+        let now = chrono::Utc::now();
+        let mut topic = topic("Budget", "upcoming", &[]);
+        topic.editors = vec!["bob".to_string()];
+        let user = |username: &str| User {
+            username: username.to_string(),
+            role: Role::Organizer,
+        };
+
+        assert!(topic.is_visible_to(None, now));
+
+        topic.publish_at = Some((now + chrono::Duration::hours(1)).to_rfc3339());
+        assert!(!topic.is_visible_to(None, now));
+        assert!(!topic.is_visible_to(Some(&user("mallory")), now));
+        assert!(topic.is_visible_to(Some(&user("alice")), now));
+        assert!(topic.is_visible_to(Some(&user("bob")), now));
+        assert!(topic.is_visible_to(None, now + chrono::Duration::hours(2)));
+
+        topic.publish_at = None;
+        topic.draft = true;
+        assert!(!topic.is_visible_to(None, now + chrono::Duration::days(365)));
+        assert!(topic.is_visible_to(Some(&user("bob")), now));
+    }
+
+    #[test]
+    fn only_stored_uploads_are_linked() {
+        assert!(is_upload_name("0f8e2a1c-5b7d-4e3a-9c6f-1d2e3f4a5b6c.png"));
+        assert!(is_upload_name(
+            "0f8e2a1c-5b7d-4e3a-9c6f-1d2e3f4a5b6c-thumb.jpg"
+        ));
+        for name in [
+            "",
+            ".",
+            "..",
+            "..png",
+            ".hidden.png",
+            "a/b.png",
+            "a\\b.png",
+            "a b.png",
+        ] {
+            assert!(!is_upload_name(name), "{name:?}");
+        }
+
+        assert_eq!(upload_name("/uploads/1a2b.pdf"), Some("1a2b.pdf"));
+        for url in [
+            "javascript:alert(1)",
+            "https://evil.example/uploads/1a2b.pdf",
+            "//evil.example/1a2b.pdf",
+            "/uploads/../secret.pdf",
+            "/uploads/",
+            "/uploadsx/1a2b.pdf",
+        ] {
+            assert_eq!(upload_name(url), None, "{url:?}");
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn topics_may_only_refer_to_stored_uploads() {
+        let attachment = |url: &str| Attachment {
+            url: url.to_string(),
+            name: "minutes.pdf".to_string(),
+            size: 1,
+        };
+        let option = |url: &str, thumbnail_url: &str| CreateOptionInput {
+            image: Some(Image {
+                url: url.to_string(),
+                thumbnail_url: thumbnail_url.to_string(),
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            upload_names(
+                &[attachment("/uploads/a.pdf")],
+                &[
+                    option("/uploads/b.png", "/uploads/b-thumb.jpg"),
+                    Default::default()
+                ],
+            )
+            .unwrap(),
+            ["a.pdf", "b.png", "b-thumb.jpg"]
+        );
+        assert!(upload_names(&[attachment("javascript:alert(1)")], &[]).is_err());
+        assert!(upload_names(&[], &[option("/uploads/b.png", "data:text/html,hi")]).is_err());
+    }
+
+    #[test]
+    fn stage_follows_the_clock() {
+        let topic = topic("Budget", "upcoming", &[]);
+        let at = |time: &str| topic.stage_at(time.parse().unwrap());
+
+        assert_eq!(at("2023-12-31T23:59:59Z"), Some(Stage::Upcoming));
+        assert_eq!(at("2024-01-01T00:00:00Z"), Some(Stage::Voting));
+        assert_eq!(at("2024-01-07T23:59:59Z"), Some(Stage::Voting));
+        assert_eq!(at("2024-01-08T00:00:00Z"), Some(Stage::Ended));
+        // the offset of the times does not matter
+        assert_eq!(
+            Topic {
+                starts_at: "2024-01-01T08:00:00+08:00".to_string(),
+                ..topic.clone()
+            }
+            .stage_at("2024-01-01T00:00:00Z".parse().unwrap()),
+            Some(Stage::Voting)
+        );
+    }
+
+    #[test]
+    fn stage_falls_back_to_the_backend() {
+        let now = chrono::Utc::now();
+        let unscheduled = Topic {
+            starts_at: String::new(),
+            ..topic("Budget", "Voting", &[])
+        };
+        assert_eq!(unscheduled.stage_at(now), Some(Stage::Voting));
+
+        let unknown = Topic {
+            ends_at: "next week".to_string(),
+            ..topic("Budget", "archived", &[])
+        };
+        assert_eq!(unknown.stage_at(now), None);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::api::{get_me, Role};
 use crate::component::*;
+use crate::i18n::{provide_i18n, TIME_ZONE_ATTRIBUTE};
 use crate::page::*;
 use crate::state::GlobalState;
 use leptos::*;
@@ -9,18 +11,59 @@ use leptos_router::*;
 pub fn App(cx: Scope) -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context(cx);
+    let i18n = provide_i18n(cx);
+    // shared by every countdown, rather than a timer each
+    provide_clock(cx);
 
-    provide_context(cx, create_rw_signal(cx, GlobalState::new(cx)));
+    // an active render until the runtime of this request is dropped after streaming
+    #[cfg(feature = "ssr")]
+    store_value(cx, crate::server::metrics::ActiveRender::start());
+
+    let state = create_rw_signal(cx, GlobalState::new(cx));
+    provide_context(cx, state);
+
+    // keep the current user (and their role) in sync with the token
+    let me = create_local_resource(
+        cx,
+        move || state.with(|s| s.token().map(|t| t.to_string())),
+        |token| async move {
+            match token {
+                // e.g. an expired token, the user is then treated as logged out
+                Some(token) => get_me(token)
+                    .await
+                    .map_err(|e| error!("loading the current user failed: {e}"))
+                    .ok(),
+                None => None,
+            }
+        },
+    );
+    create_effect(cx, move |_| {
+        if let Some(user) = me.read(cx) {
+            state.update(|s| s.set_user(user));
+        }
+    });
+
+    // hashed in release builds, the client picks up the <link> rendered by the server
+    #[cfg(feature = "ssr")]
+    let stylesheet = crate::server::assets::stylesheet();
+    #[cfg(not(feature = "ssr"))]
+    let stylesheet = "/pkg/vote.css".to_string();
 
     view! { cx,
         // injects a stylesheet into the document <head>
         // id=leptos means cargo-leptos will hot-reload this stylesheet
-        <Stylesheet id="leptos" href="/pkg/leptos_start.css"/>
+        // Tailwind and DaisyUI are compiled into it, see `style/main.scss`
+        <Stylesheet id="leptos" href=stylesheet/>
+        // read back by the client when hydrating, see `i18n`
+        <Html
+            lang=i18n.locale().tag()
+            attributes=AdditionalAttributes::from(vec![(
+                TIME_ZONE_ATTRIBUTE,
+                i18n.time_zone().to_string(),
+            )])
+        />
         // sets the document title
-        <Title text="NTNU CSIE Online Voting System" />
-        // Load tailwind css
-        <link href="https://cdn.jsdelivr.net/npm/daisyui@3.1.1/dist/full.css" rel="stylesheet" type="text/css" />
-        <Script src="https://cdn.tailwindcss.com"></Script>
+        <Title text=i18n.t("site-title") />
 
         // content for this welcome page
         <Router>
@@ -28,10 +71,29 @@ pub fn App(cx: Scope) -> impl IntoView {
                 <NavBar />
                 <Routes>
                     <Route path="" view=|cx| view! { cx, <HomePage/> }/>
-                    <Route path="/topic/create" view=|cx| view! { cx, <CreateTopicPage/> }/>
+                    <Route path="/topics" view=|cx| view! { cx, <TopicsPage/> }/>
+                    <Route path="/tag/:name" view=|cx| view! { cx, <TopicsPage/> }/>
+                    <Route path="/topic/create" view=|cx| view! { cx,
+                        <Guard role=Role::Organizer><CreateTopicPage/></Guard>
+                    }/>
                     <Route path="/topic/:id" view=|cx| view! { cx, <TopicPage/> }/>
                     <Route path="/login" view=|cx| view! { cx, <LoginPage/> }/>
+                    <Route path="/login/oidc" view=|cx| view! { cx, <OidcCallbackPage/> }/>
                     <Route path="/signup" view=|cx| view! { cx, <SignupPage/> }/>
+                    <Route path="/forgot-password" view=|cx| view! { cx, <ForgotPasswordPage/> }/>
+                    <Route path="/reset-password" view=|cx| view! { cx, <ResetPasswordPage/> }/>
+                    <Route path="/verify-email" view=|cx| view! { cx, <VerifyEmailPage/> }/>
+                    <Route path="/me" view=|cx| view! { cx, <Guard><ProfilePage/></Guard> }/>
+                    <Route path="/me/password" view=|cx| view! { cx, <Guard><ChangePasswordPage/></Guard> }/>
+                    <Route path="/me/drafts" view=|cx| view! { cx,
+                        <Guard role=Role::Organizer><DraftsPage/></Guard>
+                    }/>
+                    <Route path="/admin/moderation" view=|cx| view! { cx,
+                        <Guard role=Role::Admin><ModerationPage/></Guard>
+                    }/>
+                    <Route path="/admin/tags" view=|cx| view! { cx,
+                        <Guard role=Role::Admin><TagsPage/></Guard>
+                    }/>
                 </Routes>
             </main>
         </Router>
@@ -1,14 +1,140 @@
-use crate::api::{Comment, CreateComment, CreateCommentInput};
+use crate::api::{
+    get_reactions, Comment, CreateComment, CreateCommentInput, GetReactionsInput, ReportComment,
+    ReportCommentInput, SetReaction, SetReactionInput, REACTION_EMOJIS,
+};
+use crate::component::{create_cooldown, CooldownAlert, ErrorList};
+use crate::i18n::use_i18n;
 use crate::state::GlobalState;
 use leptos::ev::MouseEvent;
 use leptos::*;
 
 #[component]
 pub fn CommentCard(cx: Scope, #[prop(into)] comment: Signal<Comment>) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let is_login = move || state().token().is_some();
+
+    let set_reaction = create_server_action::<SetReaction>(cx);
+    let set_reaction_pending = set_reaction.pending();
+    let set_reaction_version = set_reaction.version();
+    let set_reaction_result = set_reaction.value();
+    let reactions = create_local_resource(
+        cx,
+        move || {
+            (
+                state().token().map(|t| t.to_string()),
+                comment().id,
+                set_reaction_version(),
+            )
+        },
+        |(token, comment_id, _)| async move {
+            get_reactions(token, GetReactionsInput { comment_id }).await
+        },
+    );
+    let reaction_buttons = move || {
+        // no counts until they are loaded
+        let reactions = reactions.read(cx).unwrap_or(Ok(vec![]))?;
+        let buttons = REACTION_EMOJIS
+            .iter()
+            .map(|emoji| {
+                let (count, reacted) = reactions
+                    .iter()
+                    .find(|r| r.emoji == *emoji)
+                    .map(|r| (r.count, r.reacted))
+                    .unwrap_or_default();
+                let react = move |_| {
+                    if !is_login() || set_reaction_pending() {
+                        return;
+                    }
+                    set_reaction.dispatch(SetReaction {
+                        token: state().token().unwrap().to_string(),
+                        input: SetReactionInput {
+                            comment_id: comment().id,
+                            emoji: emoji.to_string(),
+                            reacted: !reacted,
+                        },
+                    });
+                };
+                view! { cx,
+                    <button
+                        class="btn btn-sm"
+                        class:btn-primary=reacted
+                        class:btn-ghost={!reacted}
+                        class:btn-disabled=move || !is_login()
+                        on:click=react
+                    >
+                        {*emoji}" "{count}
+                    </button>
+                }
+            })
+            .collect_view(cx);
+
+        Ok::<_, ServerFnError>(buttons)
+    };
+
+    let report_comment = create_server_action::<ReportComment>(cx);
+    let report_result = report_comment.value();
+    let (reporting, set_reporting) = create_signal(cx, false);
+    let (reason, set_reason) = create_signal(cx, "".to_string());
+    let submit_report = move |_| {
+        if !is_login() || reason().trim().is_empty() {
+            return;
+        }
+
+        report_comment.dispatch(ReportComment {
+            token: state().token().unwrap().to_string(),
+            input: ReportCommentInput {
+                comment_id: comment().id,
+                reason: reason(),
+            },
+        });
+
+        set_reporting(false);
+        set_reason("".to_string());
+    };
+    let report_form = move || {
+        reporting().then(|| {
+            view! { cx,
+                <div class="flex flex-col gap-2 mt-2">
+                    <input
+                        type="text"
+                        class="input input-bordered input-sm w-full"
+                        placeholder=i18n.t("comment-report-reason")
+                        on:input=move |ev| set_reason(event_target_value(&ev))
+                        prop:value=reason
+                    />
+                    <button class="btn btn-sm btn-warning" on:click=submit_report>
+                        {i18n.t("comment-report-submit")}
+                    </button>
+                </div>
+            }
+        })
+    };
+
     view! { cx,
         <div class="card">
             <div class="card-body">
                 <p>{comment().content}</p>
+                <div class="flex flex-wrap items-center gap-1">
+                    <ErrorList error_title=i18n.t("comment-reaction-failed")>
+                        {reaction_buttons}
+                        {move || set_reaction_result().map(|r| r.map(|_| ()))}
+                    </ErrorList>
+                    {move || is_login().then(|| view! { cx,
+                        <button
+                            class="btn btn-sm btn-ghost ml-auto"
+                            on:click=move |_| set_reporting.update(|r| *r = !*r)
+                        >
+                            {i18n.t("comment-report")}
+                        </button>
+                    })}
+                </div>
+                {report_form}
+                <ErrorList error_title=i18n.t("comment-report-failed")>
+                    {move || report_result().map(|r| r.map(|_| view! { cx,
+                        <p class="text-sm text-success">{i18n.t("comment-reported")}</p>
+                    }))}
+                </ErrorList>
             </div>
         </div>
     }
@@ -19,15 +145,26 @@ pub fn CreateCommentCard<F>(cx: Scope, id: String, after_submit: F) -> impl Into
 where
     F: Fn(MouseEvent) + 'static,
 {
+    let i18n = use_i18n(cx);
     let (id, _) = create_signal(cx, id);
     let (content, set_content) = create_signal(cx, "".to_string());
     let create_comment = create_server_action::<CreateComment>(cx);
     let create_comment_pending = create_comment.pending();
+    let create_comment_result = create_comment.value();
     let state = expect_context::<RwSignal<GlobalState>>(cx);
     let is_login = move || state().token().is_some();
 
+    // comments are rate limited, tell how long to wait after a rejected one
+    let (cooldown, check_cooldown) = create_cooldown(cx, "create_comment");
+    create_effect(cx, move |_| {
+        if let Some(Err(_)) = create_comment_result() {
+            check_cooldown(state.with_untracked(|s| s.token().map(|t| t.to_string())));
+        }
+    });
+    let can_submit = move || is_login() && cooldown() == 0;
+
     let on_submit = move |ev| {
-        if !is_login() {
+        if !can_submit() {
             return;
         }
 
@@ -46,19 +183,19 @@ where
     let submit_btn_label = move || {
         if is_login() {
             if create_comment_pending() {
-                "Loading..."
+                i18n.t("loading")
             } else {
-                "Submit"
+                i18n.t("submit")
             }
         } else {
-            "Login to continue"
+            i18n.t("login-to-continue")
         }
     };
 
     view! { cx,
         <div class="card">
             <div class="card-body">
-                <h2 class="card-title">"Leave your comment here:"</h2>
+                <h2 class="card-title">{i18n.t("comment-create-title")}</h2>
                 <textarea
                     class="textarea my-2"
                     on:input = move |ev| {
@@ -67,10 +204,11 @@ where
                     prop:value=content
                 />
             </div>
+            <CooldownAlert seconds=cooldown/>
             <div class="card-actions">
                 <button
-                    class:btn-disabled=move || !is_login()
-                    class:btn-primary=is_login
+                    class:btn-disabled=move || !can_submit()
+                    class:btn-primary=can_submit
                     on:click=on_submit
                     class="btn w-full"
                 >
@@ -0,0 +1,62 @@
+use crate::api::{get_cooldown, GetCooldownInput};
+use crate::i18n::use_i18n;
+use leptos::leptos_dom::helpers::set_interval_with_handle;
+use leptos::*;
+use std::time::Duration;
+
+/// Seconds left until a rate limited server fn may be called again
+///
+/// Call the returned `check` with the token or username after a failed call, the
+/// signal then counts down to zero.
+pub fn create_cooldown(
+    cx: Scope,
+    server_fn: &'static str,
+) -> (ReadSignal<u64>, impl Fn(Option<String>) + Copy) {
+    let (seconds, set_seconds) = create_signal(cx, 0u64);
+
+    // effects only run in the browser
+    create_effect(cx, move |_| {
+        let handle = set_interval_with_handle(
+            move || {
+                if seconds.get_untracked() > 0 {
+                    set_seconds.update(|s| *s -= 1);
+                }
+            },
+            Duration::from_secs(1),
+        );
+        if let Ok(handle) = handle {
+            on_cleanup(cx, move || handle.clear());
+        }
+    });
+
+    let check = move |user: Option<String>| {
+        spawn_local(async move {
+            let input = GetCooldownInput {
+                server_fn: server_fn.to_string(),
+                user,
+            };
+            match get_cooldown(cx, input).await {
+                Ok(cooldown) => set_seconds(cooldown),
+                // the next call is rejected with its own error anyway
+                Err(e) => error!("getting the cooldown of {server_fn} failed: {e}"),
+            }
+        })
+    };
+
+    (seconds, check)
+}
+
+#[component]
+pub fn CooldownAlert(cx: Scope, #[prop(into)] seconds: Signal<u64>) -> impl IntoView {
+    let i18n = use_i18n(cx);
+
+    move || {
+        (seconds() > 0).then(|| {
+            view! { cx,
+                <div class="alert alert-warning my-2">
+                    {i18n.t_args("cooldown", [("seconds", seconds().into())])}
+                </div>
+            }
+        })
+    }
+}
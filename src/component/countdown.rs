@@ -0,0 +1,127 @@
+use crate::api::{Stage, Topic};
+use crate::i18n::{use_i18n, I18n};
+use chrono::{DateTime, Utc};
+use leptos::leptos_dom::helpers::set_interval_with_handle;
+use leptos::*;
+use std::time::Duration;
+
+/// The current time, ticking every second in the browser
+fn create_now(cx: Scope) -> ReadSignal<DateTime<Utc>> {
+    let (now, set_now) = create_signal(cx, Utc::now());
+
+    // effects only run in the browser
+    create_effect(cx, move |_| {
+        let handle = set_interval_with_handle(move || set_now(Utc::now()), Duration::from_secs(1));
+        if let Ok(handle) = handle {
+            on_cleanup(cx, move || handle.clear());
+        }
+    });
+
+    now
+}
+
+/// One clock for the whole page, see [`use_now`]
+#[derive(Clone, Copy)]
+struct Clock(ReadSignal<DateTime<Utc>>);
+
+/// Start the clock every countdown of the page follows
+pub fn provide_clock(cx: Scope) {
+    provide_context(cx, Clock(create_now(cx)));
+}
+
+/// The current time from [`provide_clock`], or from a clock of its own outside of it
+pub fn use_now(cx: Scope) -> ReadSignal<DateTime<Utc>> {
+    use_context::<Clock>(cx).map_or_else(|| create_now(cx), |clock| clock.0)
+}
+
+/// `2d 03:04:05`, or `03:04:05` if less than a day is left
+pub fn format_remaining(i18n: I18n, remaining: chrono::Duration) -> String {
+    let seconds = remaining.num_seconds().max(0);
+    let (days, hours, minutes, seconds) = (
+        seconds / 86_400,
+        seconds / 3_600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+    );
+
+    if days > 0 {
+        let days = i18n.t_args("countdown-days", [("days", days.into())]);
+        format!("{days} {hours:02}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+#[component]
+pub fn StageBadge(cx: Scope, #[prop(into)] stage: Signal<Option<Stage>>) -> impl IntoView {
+    let i18n = use_i18n(cx);
+
+    move || {
+        stage().map(|stage| {
+            let class = match stage {
+                Stage::Upcoming => "badge badge-info",
+                Stage::Voting => "badge badge-success",
+                Stage::Ended => "badge badge-ghost",
+            };
+            view! { cx, <span class=class>{i18n.t(stage.message_id())}</span> }
+        })
+    }
+}
+
+/// Time left until voting on `topic` starts or closes
+#[component]
+pub fn Countdown(
+    cx: Scope,
+    #[prop(into)] topic: Signal<Topic>,
+    #[prop(into)] now: Signal<DateTime<Utc>>,
+) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let remaining = move || {
+        let now = now();
+        let topic = topic();
+        let until = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .map(|time| format_remaining(i18n, time.with_timezone(&Utc) - now))
+                .unwrap_or_default()
+        };
+        match topic.stage_at(now)? {
+            Stage::Upcoming => Some(i18n.t_args(
+                "countdown-starts-in",
+                [("remaining", until(&topic.starts_at).into())],
+            )),
+            Stage::Voting => Some(i18n.t_args(
+                "countdown-closes-in",
+                [("remaining", until(&topic.ends_at).into())],
+            )),
+            Stage::Ended => Some(i18n.t("countdown-closed")),
+        }
+    };
+
+    view! { cx,
+        <span class="font-mono" role="timer">{remaining}</span>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Locale;
+
+    #[test]
+    fn format_remaining_time() {
+        let i18n = I18n::new(Locale::En);
+        let remaining = |seconds| format_remaining(i18n, chrono::Duration::seconds(seconds));
+
+        assert_eq!(remaining(0), "00:00:00");
+        assert_eq!(remaining(59), "00:00:59");
+        assert_eq!(remaining(3_600 + 2 * 60 + 3), "01:02:03");
+        assert_eq!(remaining(86_399), "23:59:59");
+        assert_eq!(remaining(86_400), "1d 00:00:00");
+        assert_eq!(
+            remaining(2 * 86_400 + 3 * 3_600 + 4 * 60 + 5),
+            "2d 03:04:05"
+        );
+        // a clock running a little behind the times
+        assert_eq!(remaining(-5), "00:00:00");
+    }
+}
@@ -1,11 +1,23 @@
+use crate::i18n::{use_i18n, I18n};
 use leptos::*;
 
+/// Server fns fail with message ids where they can, see `validate::Invalid`, anything
+/// else is shown as it is
+fn message(i18n: I18n, error: &(dyn std::error::Error + Send + Sync + 'static)) -> String {
+    match error.downcast_ref::<ServerFnError>() {
+        Some(ServerFnError::ServerError(message)) => i18n.t(message),
+        _ => error.to_string(),
+    }
+}
+
 #[component]
 pub fn ErrorList(
     cx: Scope,
     children: Children,
-    #[prop(default = "Error Occurred!".to_string())] error_title: String,
+    #[prop(optional)] error_title: Option<String>,
 ) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let error_title = error_title.unwrap_or_else(|| i18n.t("error-title"));
     let (error_title, _) = create_signal(cx, error_title);
 
     view! { cx,
@@ -19,7 +31,7 @@ pub fn ErrorList(
                         <ul>
                             {move || errors.get()
                                 .into_iter()
-                                .map(|(_, e)| view! { cx, <li>{e.to_string()}</li>})
+                                .map(|(_, e)| view! { cx, <li>{message(i18n, &e)}</li>})
                                 .collect_view(cx)
                             }
                         </ul>
@@ -0,0 +1,92 @@
+use crate::api::Role;
+use crate::i18n::use_i18n;
+use crate::state::GlobalState;
+use crate::uri::encode_component;
+use leptos::*;
+use leptos_router::*;
+
+/// Where to go after logging in, only same-site paths are accepted to avoid open redirects.
+///
+/// Browsers treat `/\host` like `//host` and drop tabs and newlines from URLs, so those are
+/// rejected as well.
+pub fn safe_next(next: Option<String>) -> String {
+    next.filter(|next| {
+        next.starts_with('/')
+            && !next.starts_with("//")
+            && !next.starts_with("/\\")
+            && !next.chars().any(char::is_control)
+    })
+    .unwrap_or_else(|| "/".to_string())
+}
+
+/// Path to the login page, which sends the user back to `next` after logging in
+pub fn login_path(next: &str) -> String {
+    format!("/login?next={}", encode_component(next))
+}
+
+/// Only renders `children` for logged in users with at least `role`.
+/// Anonymous users are redirected to the login page and brought back here afterwards.
+#[component]
+pub fn Guard(cx: Scope, #[prop(optional)] role: Role, children: ChildrenFn) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let location = use_location(cx);
+    // only re-render when the login status or role actually changes
+    let status = create_memo(cx, move |_| state.with(|s| (s.is_anonymous(), s.role())));
+
+    move || match status() {
+        (true, _) => {
+            let pathname = location.pathname.get_untracked();
+            let search = location.search.get_untracked();
+            let search = search.trim_start_matches('?');
+            let next = if search.is_empty() {
+                pathname
+            } else {
+                format!("{pathname}?{search}")
+            };
+            view! { cx, <Redirect path=login_path(&next) /> }.into_view(cx)
+        }
+        (false, None) => view! { cx,
+            <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p>
+        }
+        .into_view(cx),
+        (false, Some(current)) if current.allows(role) => children(cx).into_view(cx),
+        (false, Some(_)) => view! { cx,
+            <div class="alert alert-error max-w-md mx-auto mt-8">
+                {i18n.t_args("guard-role-required", [("role", i18n.t(role.message_id()).into())])}
+            </div>
+        }
+        .into_view(cx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(value: &str) -> String {
+        safe_next(Some(value.to_string()))
+    }
+
+    #[test]
+    fn safe_next_keeps_same_site_paths() {
+        assert_eq!(next("/topic/1"), "/topic/1");
+        assert_eq!(next("/?tag=a&page=2"), "/?tag=a&page=2");
+        assert_eq!(safe_next(None), "/");
+    }
+
+    #[test]
+    fn safe_next_rejects_other_sites() {
+        for value in [
+            "https://evil.com",
+            "//evil.com",
+            "/\\evil.com",
+            "/\t/evil.com",
+            "/\n/evil.com",
+            "evil.com",
+            "",
+        ] {
+            assert_eq!(next(value), "/", "{value:?}");
+        }
+    }
+}
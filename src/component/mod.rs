@@ -1,67 +1,101 @@
 pub mod comment;
+pub mod cooldown;
+pub mod countdown;
 pub mod error;
+pub mod guard;
+pub mod pagination;
+pub mod tag;
 pub mod topic;
+pub mod upload;
 
 pub use comment::*;
+pub use cooldown::*;
+pub use countdown::*;
 pub use error::*;
+pub use guard::*;
+pub use pagination::*;
+pub use tag::*;
 pub use topic::*;
+pub use upload::*;
 
-use crate::api::get_me;
+use crate::api::Role;
+use crate::i18n::{switch_locale, use_i18n, Locale};
 use crate::state::GlobalState;
 use leptos::*;
 
 #[component]
 pub fn NavBar(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
     let state = expect_context::<RwSignal<GlobalState>>(cx);
-    let user = create_resource(cx, state.read_only(), |state| async move {
-        match state.token() {
-            Some(token) => Some(get_me(token.to_string()).await),
-            None => None,
+    let username = move || state.with(|s| s.user().map(|u| u.username.clone()));
+    let is_organizer = move || state.with(|s| s.has_role(Role::Organizer));
+    let is_admin = move || state.with(|s| s.has_role(Role::Admin));
+    let avatar = move || match username() {
+        Some(username) => view! { cx,
+            <div class="flex items-center">
+                <a href="/me" class="btn btn-ghost text-gray-300 text-sm">{username}</a>
+            </div>
         }
-    });
-    let username = move || user.read(cx).and_then(|u| u).map(|u| u.map(|u| u.username));
-    let avatar = move || {
-        view! { cx,
-            <Transition fallback=move || view! { cx, <p>"Loading..."</p> }>
-                <ErrorBoundary
-                    // FIXME: error handling
-                    fallback=move |cx, errors| view! { cx,
-                        <a href="/login" class="btn btn-ghost">"Login"</a>
-                    }
-                >
-                    {move || match username() {
-                        Some(username) => {
-                            view! { cx,
-                                <div class="flex items-center">
-                                    <span class="text-gray-300 text-sm pr-2">{username}</span>
-                                </div>
-                            }.into_view(cx)
-                        }
-                        None => {
-                            view! { cx,
-                                <a href="/login" class="btn btn-ghost">"Login"</a>
-                            }.into_view(cx)
-                        }
-                    }}
-                </ErrorBoundary>
-            </Transition>
+        .into_view(cx),
+        None => view! { cx,
+            <a href="/login" class="btn btn-ghost">{i18n.t("nav-login")}</a>
         }
+        .into_view(cx),
     };
 
     view! { cx,
         <nav class="navbar bg-gray-800 px-2">
             <div class="flex-1">
-                <a href="/" class="btn btn-ghost text-xl">"Voting System"</a>
+                <a href="/" class="btn btn-ghost text-xl">{i18n.t("nav-home")}</a>
                 <div class="hidden md:block">
                     <div class="ml-4 flex items-center space-x-4">
-                        <a href="/topics" class="btn btn-ghost">"Topics"</a>
-                        <a href="/about" class="btn btn-ghost">"About"</a>
+                        <a href="/topics" class="btn btn-ghost">{i18n.t("nav-topics")}</a>
+                        {move || is_organizer().then(|| view! { cx,
+                            <a href="/topic/create" class="btn btn-ghost">{i18n.t("nav-new-topic")}</a>
+                            <a href="/me/drafts" class="btn btn-ghost">{i18n.t("nav-drafts")}</a>
+                        })}
+                        {move || is_admin().then(|| view! { cx,
+                            <a href="/admin/moderation" class="btn btn-ghost">{i18n.t("nav-moderation")}</a>
+                            <a href="/admin/tags" class="btn btn-ghost">{i18n.t("nav-tags")}</a>
+                        })}
+                        <a href="/about" class="btn btn-ghost">{i18n.t("nav-about")}</a>
                     </div>
                 </div>
             </div>
-            <div class="flex-none">
+            <div class="flex-none gap-2">
+                <LanguageSwitcher />
                 {avatar}
             </div>
         </nav>
     }
 }
+
+/// Picks the language of the UI, reloading the page in it
+#[component]
+fn LanguageSwitcher(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let on_change = move |ev| {
+        if let Ok(locale) = event_target_value(&ev).parse::<Locale>() {
+            if locale != i18n.locale() {
+                switch_locale(locale);
+            }
+        }
+    };
+
+    view! { cx,
+        <select
+            class="select select-ghost select-sm"
+            aria-label=i18n.t("nav-language")
+            on:change=on_change
+        >
+            {Locale::ALL
+                .into_iter()
+                .map(|locale| view! { cx,
+                    <option value=locale.tag() selected={locale == i18n.locale()}>
+                        {locale.native_name()}
+                    </option>
+                })
+                .collect_view(cx)}
+        </select>
+    }
+}
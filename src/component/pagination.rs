@@ -0,0 +1,40 @@
+use crate::i18n::use_i18n;
+use leptos::*;
+
+/// Previous / next buttons around the current page number, pages are 1-based
+#[component]
+pub fn Pagination<F>(
+    cx: Scope,
+    #[prop(into)] page: Signal<usize>,
+    #[prop(into)] page_count: Signal<usize>,
+    on_change: F,
+) -> impl IntoView
+where
+    F: Fn(usize) + Copy + 'static,
+{
+    let i18n = use_i18n(cx);
+    let has_prev = move || page() > 1;
+    let has_next = move || page() < page_count();
+
+    view! { cx,
+        <div class="join flex justify-center my-4">
+            <button
+                class="join-item btn"
+                class:btn-disabled=move || !has_prev()
+                on:click=move |_| if has_prev() { on_change(page() - 1) }
+            >
+                "«"
+            </button>
+            <button class="join-item btn">
+                {move || i18n.t_args("pagination", [("page", page().into()), ("count", page_count().max(1).into())])}
+            </button>
+            <button
+                class="join-item btn"
+                class:btn-disabled=move || !has_next()
+                on:click=move |_| if has_next() { on_change(page() + 1) }
+            >
+                "»"
+            </button>
+        </div>
+    }
+}
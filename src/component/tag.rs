@@ -0,0 +1,115 @@
+use crate::api::{get_tags, normalize_tag, normalize_tags};
+use crate::i18n::use_i18n;
+use crate::uri::encode_component;
+use leptos::*;
+
+/// Most suggestions [`TagPicker`] shows at once
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Link to the topics of `tag`
+pub fn tag_href(tag: &str) -> String {
+    format!("/tag/{}", encode_component(tag))
+}
+
+/// Tags of a topic, each linking to the other topics with it
+#[component]
+pub fn TagChips(cx: Scope, tags: Vec<String>) -> impl IntoView {
+    view! { cx,
+        <div class="flex flex-wrap gap-1">
+            {normalize_tags(&tags)
+                .into_iter()
+                .map(|tag| view! { cx,
+                    <a href=tag_href(&tag) class="badge badge-outline badge-info">{tag}</a>
+                })
+                .collect_view(cx)}
+        </div>
+    }
+}
+
+/// Text input adding tags to `tags`, suggesting the tags already in use
+///
+/// A tag is added with Enter or `,`, or by picking a suggestion.
+#[component]
+pub fn TagPicker(cx: Scope, tags: RwSignal<Vec<String>>) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let (text, set_text) = create_signal(cx, String::new());
+    let known_tags = create_resource(cx, || (), |_| get_tags());
+
+    let add = move |tag: &str| {
+        let tag = normalize_tag(tag);
+        if !tag.is_empty() {
+            tags.update(|tags| {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            });
+        }
+        set_text(String::new());
+    };
+    let on_keydown = move |ev: ev::KeyboardEvent| {
+        if ev.key() == "Enter" || ev.key() == "," {
+            // not submitting the form
+            ev.prevent_default();
+            add(&text.get_untracked());
+        }
+    };
+    let suggestions = move || {
+        let typed = normalize_tag(&text());
+        let known = known_tags.read(cx).and_then(|t| t.ok()).unwrap_or_default();
+        known
+            .into_iter()
+            .map(|t| t.name)
+            .filter(|name| name.contains(&typed) && !tags.with(|tags| tags.contains(name)))
+            .take(MAX_SUGGESTIONS)
+            .map(|name| {
+                let label = name.clone();
+                view! { cx,
+                    <button
+                        type="button"
+                        class="badge badge-ghost cursor-pointer"
+                        on:click=move |_| add(&name)
+                    >
+                        "+ "{label}
+                    </button>
+                }
+            })
+            .collect_view(cx)
+    };
+    let chosen = move || {
+        tags()
+            .into_iter()
+            .map(|tag| {
+                let name = tag.clone();
+                let label = i18n.t_args("tag-remove", [("tag", tag.as_str().into())]);
+                view! { cx,
+                    <span class="badge badge-info gap-1">
+                        {name}
+                        <button
+                            type="button"
+                            aria-label=label
+                            on:click=move |_| tags.update(|tags| tags.retain(|t| *t != tag))
+                        >
+                            "✕"
+                        </button>
+                    </span>
+                }
+            })
+            .collect_view(cx)
+    };
+
+    view! { cx,
+        <div class="flex flex-wrap gap-1 mb-2">{chosen}</div>
+        <input
+            type="text"
+            id="tags"
+            class="input input-bordered input-info w-full max-w-md"
+            placeholder=i18n.t("tag-placeholder")
+            prop:value=text
+            on:input=move |ev| set_text(event_target_value(&ev))
+            on:keydown=on_keydown
+        />
+        <Transition fallback=|| ()>
+            <div class="flex flex-wrap gap-1 mt-2">{suggestions}</div>
+        </Transition>
+    }
+}
@@ -1,4 +1,7 @@
-use crate::api::{Topic, VoteOption};
+use crate::api::{Stage, Topic, TopicQuery, TopicSort, VoteOption};
+use crate::component::{use_now, Countdown, StageBadge, TagChips, Thumbnail};
+use crate::i18n::use_i18n;
+use crate::uri::encode_component;
 use leptos::*;
 use leptos_router::*;
 
@@ -8,7 +11,12 @@ pub fn TopicCard(
     #[prop(into)] topic: Signal<Topic>,
     #[prop(optional)] show_action: bool,
 ) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let now = use_now(cx);
+    let stage = Signal::derive(cx, move || topic.with(|t| t.stage_at(now())));
+    let countdown = view! { cx, <Countdown topic=topic now=now /> };
     let topic = topic();
+    let tags = (!topic.tags.is_empty()).then(|| view! { cx, <TagChips tags=topic.tags /> });
     let goto = use_navigate(cx);
     let open_topic = move |_| {
         // FIXME: error handling
@@ -17,7 +25,7 @@ pub fn TopicCard(
     let action = show_action.then(|| {
         view! { cx,
             <div class="card-actions justify-end">
-            <button on:click=open_topic class="btn btn-primary">"Detail"</button>
+            <button on:click=open_topic class="btn btn-primary">{i18n.t("topic-detail")}</button>
             </div>
         }
     });
@@ -25,11 +33,15 @@ pub fn TopicCard(
         <div class="card w-96 bg-base-200 mb-4 shadow-xl">
             <div class="card-body">
                 <div class="text-3xl font-semibold">{topic.description}</div>
+                {tags}
+                <div class="flex items-center gap-2">
+                    <StageBadge stage=stage />
+                    {countdown}
+                </div>
                 <p>
-                    "Starts at: "{topic.starts_at} <br />
-                    "Ends at: "{topic.ends_at} <br />
-                    "Updated at: "{topic.updated_at} <br />
-                    "Stage: "{topic.stage} <br />
+                    {i18n.t("topic-starts-at")}{i18n.format_datetime(&topic.starts_at)} <br />
+                    {i18n.t("topic-ends-at")}{i18n.format_datetime(&topic.ends_at)} <br />
+                    {i18n.t("topic-updated-at")}{i18n.format_datetime(&topic.updated_at)} <br />
                     {action}
                 </p>
             </div>
@@ -48,8 +60,17 @@ where
     F: Fn() -> IV + 'static,
     IV: IntoView,
 {
+    let image = option().image.map(|image| {
+        view! { cx,
+            <figure class="pt-4">
+                <Thumbnail image=image alt=option().label />
+            </figure>
+        }
+    });
+
     view! { cx,
         <div class=format!("card card-compact w-96 m-4 {extra_class}")>
+            {image}
             <div class="card-body">
                 <div class="card-title">
                     {option().label}
@@ -66,3 +87,141 @@ where
         </div>
     }
 }
+
+/// Read a topic list query from the URL, `?q=..&stage=voting,ended&sort=ends`
+pub fn topic_query(params: &ParamsMap) -> TopicQuery {
+    TopicQuery {
+        search: params
+            .get("q")
+            .map(|q| q.trim().to_string())
+            .unwrap_or_default(),
+        stages: params
+            .get("stage")
+            .map(|s| s.split(',').filter_map(|s| s.parse().ok()).collect())
+            .unwrap_or_default(),
+        tag: None,
+        sort: params
+            .get("sort")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default(),
+    }
+}
+
+/// Link to `path` showing the topics of `query`, the inverse of [`topic_query`]
+pub fn topic_query_href(path: &str, query: &TopicQuery) -> String {
+    let mut params = vec![];
+    if !query.search.is_empty() {
+        params.push(format!("q={}", encode_component(&query.search)));
+    }
+    if !query.stages.is_empty() {
+        let stages = query
+            .stages
+            .iter()
+            .map(Stage::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+        params.push(format!("stage={stages}"));
+    }
+    if query.sort != TopicSort::default() {
+        params.push(format!("sort={}", query.sort.as_str()));
+    }
+
+    if params.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{}", params.join("&"))
+    }
+}
+
+/// Search box, stage chips and sort buttons of a topic list
+///
+/// Everything is kept in the URL query, so filtered lists can be shared and are
+/// rendered on the server.
+#[component]
+pub fn TopicFilters(cx: Scope, #[prop(into)] query: Signal<TopicQuery>) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let location = use_location(cx);
+    let path = move || location.pathname.get();
+    let stage_chips = move || {
+        Stage::ALL
+            .into_iter()
+            .map(|stage| {
+                let active = move || query.with(|q| q.stages.contains(&stage));
+                let href = move || {
+                    let mut query = query();
+                    if active() {
+                        query.stages.retain(|s| *s != stage);
+                    } else {
+                        query.stages.push(stage);
+                    }
+                    topic_query_href(&path(), &query)
+                };
+                view! { cx,
+                    <a
+                        href=href
+                        class="btn btn-sm rounded-full"
+                        class:btn-primary=active
+                        class:btn-outline={move || !active()}
+                    >
+                        {i18n.t(stage.message_id())}
+                    </a>
+                }
+            })
+            .collect_view(cx)
+    };
+    let sort_buttons = move || {
+        TopicSort::ALL
+            .into_iter()
+            .map(|sort| {
+                let href = move || {
+                    let query = TopicQuery { sort, ..query() };
+                    topic_query_href(&path(), &query)
+                };
+                view! { cx,
+                    <a
+                        href=href
+                        class="btn btn-sm join-item"
+                        class:btn-active={move || query.with(|q| q.sort == sort)}
+                    >
+                        {i18n.t(sort.message_id())}
+                    </a>
+                }
+            })
+            .collect_view(cx)
+    };
+    // a search keeps the chosen stages and order
+    let stages = move || {
+        query.with(|q| {
+            q.stages
+                .iter()
+                .map(Stage::as_str)
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+    };
+
+    view! { cx,
+        <div class="flex flex-col gap-2 w-full max-w-3xl mx-auto my-4">
+            <Form method="GET" action=path class="join w-full">
+                <input
+                    type="search"
+                    name="q"
+                    placeholder=i18n.t("topic-search-placeholder")
+                    class="input input-bordered join-item w-full"
+                    prop:value=move || query.with(|q| q.search.clone())
+                />
+                {move || (!stages().is_empty()).then(|| view! { cx,
+                    <input type="hidden" name="stage" value=stages />
+                })}
+                {move || query.with(|q| q.sort != TopicSort::default()).then(|| view! { cx,
+                    <input type="hidden" name="sort" value=move || query.with(|q| q.sort.as_str()) />
+                })}
+                <button type="submit" class="btn btn-primary join-item">{i18n.t("topic-search")}</button>
+            </Form>
+            <div class="flex flex-wrap items-center gap-2">
+                {stage_chips}
+                <div class="join ml-auto">{sort_buttons}</div>
+            </div>
+        </div>
+    }
+}
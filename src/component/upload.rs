@@ -0,0 +1,268 @@
+use crate::api::{upload_name, Attachment, Image, Upload};
+use crate::i18n::use_i18n;
+use crate::state::GlobalState;
+use leptos::*;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// Pictures [`ImagePicker`] offers, the server checks the content anyway
+const IMAGE_TYPES: &str = "image/png,image/jpeg,image/gif,image/webp";
+
+fn js_error(e: JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{e:?}"))
+}
+
+/// Send `file` to `/upload`, see `server::upload`
+///
+/// Only works in the browser.
+pub async fn upload_file(token: &str, file: &web_sys::File) -> Result<Upload, String> {
+    let form = web_sys::FormData::new().map_err(js_error)?;
+    form.append_with_blob_and_filename("file", file, &file.name())
+        .map_err(js_error)?;
+    let headers = web_sys::Headers::new().map_err(js_error)?;
+    headers
+        .set("Authorization", &format!("Bearer {token}"))
+        .map_err(js_error)?;
+    let mut init = web_sys::RequestInit::new();
+    init.method("POST").headers(&headers).body(Some(&form));
+
+    let resp = JsFuture::from(window().fetch_with_str_and_init("/upload", &init))
+        .await
+        .map_err(js_error)?
+        .unchecked_into::<web_sys::Response>();
+    let text = JsFuture::from(resp.text().map_err(js_error)?)
+        .await
+        .map_err(js_error)?
+        .as_string()
+        .unwrap_or_default();
+    if !resp.ok() {
+        return Err(text);
+    }
+
+    serde_json::from_str(&text).map_err(|e| e.to_string())
+}
+
+/// `1.2 MB` and the like
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit + 1 < UNITS.len() {
+        size /= 1000.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// An action uploading the file picked in an `<input type="file">`, clearing the input
+/// so the same file can be picked again
+fn create_upload(
+    cx: Scope,
+) -> (
+    Action<web_sys::File, Result<Upload, String>>,
+    impl Fn(ev::Event) + Copy,
+) {
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let upload = create_action(cx, move |file: &web_sys::File| {
+        let file = file.clone();
+        let token = state.with_untracked(|s| s.token().map(|t| t.to_string()));
+        async move {
+            let token = token.ok_or_else(|| "login required".to_string())?;
+            upload_file(&token, &file).await
+        }
+    });
+    let on_change = move |ev: ev::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        if let Some(file) = input.files().and_then(|files| files.get(0)) {
+            upload.dispatch(file);
+        }
+        input.set_value("");
+    };
+
+    (upload, on_change)
+}
+
+/// Upload progress and errors of a [`create_upload`] action
+fn upload_status(
+    cx: Scope,
+    upload: Action<web_sys::File, Result<Upload, String>>,
+) -> impl IntoView {
+    let i18n = use_i18n(cx);
+
+    move || {
+        if upload.pending()() {
+            return Some(view! { cx,
+                <span class="text-sm">{i18n.t("upload-uploading")} <span class="loading loading-spinner loading-xs"></span></span>
+            }
+            .into_view(cx));
+        }
+        match upload.value()() {
+            Some(Err(e)) => {
+                Some(view! { cx, <span class="text-sm text-error">{e}</span> }.into_view(cx))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Picture of an option, uploaded as soon as it is picked
+#[component]
+pub fn ImagePicker<F>(
+    cx: Scope,
+    #[prop(into)] image: Signal<Option<Image>>,
+    on_change: F,
+) -> impl IntoView
+where
+    F: Fn(Option<Image>) + Copy + 'static,
+{
+    let i18n = use_i18n(cx);
+    let (upload, pick) = create_upload(cx);
+    create_effect(cx, move |_| {
+        if let Some(Ok(uploaded)) = upload.value()() {
+            on_change(uploaded.image);
+        }
+    });
+
+    view! { cx,
+        <div class="mt-2 flex items-center gap-2">
+            {move || image().map(|image| view! { cx,
+                <img src=image.thumbnail_url alt=i18n.t("upload-option-picture") class="h-12 w-12 rounded object-cover" />
+                <button
+                    type="button"
+                    class="btn btn-ghost btn-xs text-error"
+                    on:click=move |_| on_change(None)
+                >
+                    {i18n.t("upload-remove-picture")}
+                </button>
+            })}
+            <input
+                type="file"
+                accept=IMAGE_TYPES
+                class="file-input file-input-bordered file-input-sm w-full max-w-xs"
+                aria-label=i18n.t("upload-option-picture")
+                on:change=pick
+            />
+            {upload_status(cx, upload)}
+        </div>
+    }
+}
+
+/// PDF documents of a topic, uploaded as soon as they are picked
+#[component]
+pub fn AttachmentPicker(cx: Scope, attachments: RwSignal<Vec<Attachment>>) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let (upload, pick) = create_upload(cx);
+    create_effect(cx, move |_| {
+        if let Some(Ok(uploaded)) = upload.value()() {
+            attachments.update(|a| a.push(uploaded.attachment));
+        }
+    });
+    let remove = move |url: String| attachments.update(|a| a.retain(|a| a.url != url));
+
+    view! { cx,
+        <ul>
+            {move || attachments()
+                .into_iter()
+                .map(|attachment| {
+                    let url = attachment.url.clone();
+                    view! { cx,
+                        <li class="flex items-center gap-2">
+                            <span class="flex-1">{attachment.name}</span>
+                            <span class="text-sm opacity-70">{format_size(attachment.size)}</span>
+                            <button
+                                type="button"
+                                class="btn btn-ghost btn-xs text-error"
+                                aria-label=i18n.t("upload-remove-document")
+                                on:click=move |_| remove(url.clone())
+                            >
+                                "✕"
+                            </button>
+                        </li>
+                    }
+                })
+                .collect_view(cx)}
+        </ul>
+        <input
+            type="file"
+            id="attachments"
+            accept="application/pdf"
+            class="file-input file-input-bordered file-input-info w-full max-w-md"
+            on:change=pick
+        />
+        {upload_status(cx, upload)}
+    }
+}
+
+/// Thumbnail of a picture, showing it in full in a lightbox when clicked
+///
+/// Nothing is shown unless both files were stored by `/upload`.
+#[component]
+pub fn Thumbnail(cx: Scope, image: Image, #[prop(into)] alt: String) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let (open, set_open) = create_signal(cx, false);
+    if upload_name(&image.url).is_none() || upload_name(&image.thumbnail_url).is_none() {
+        return ().into_view(cx);
+    }
+
+    view! { cx,
+        <button type="button" class="block" on:click=move |_| set_open(true)>
+            <img
+                src=image.thumbnail_url
+                alt=alt.clone()
+                loading="lazy"
+                class="rounded-lg max-h-48 object-cover"
+            />
+        </button>
+        <div class="modal" class:modal-open=open on:click=move |_| set_open(false)>
+            {move || open().then(|| view! { cx,
+                <div class="modal-box max-w-5xl p-0">
+                    <img src=image.url.clone() alt=alt.clone() class="w-full" />
+                </div>
+            })}
+            <button type="button" class="btn btn-circle btn-sm absolute right-4 top-4" aria-label=i18n.t("close")>
+                "✕"
+            </button>
+        </div>
+    }
+    .into_view(cx)
+}
+
+/// Documents of a topic as download links
+///
+/// Only files stored by `/upload` are linked, the server rejects other URLs anyway.
+#[component]
+pub fn AttachmentList(cx: Scope, attachments: Vec<Attachment>) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let attachments = attachments
+        .into_iter()
+        .filter(|attachment| upload_name(&attachment.url).is_some())
+        .collect::<Vec<_>>();
+
+    (!attachments.is_empty()).then(|| {
+        view! { cx,
+            <div class="card w-96 bg-base-200 mb-4">
+                <div class="card-body">
+                    <div class="card-title">{i18n.t("upload-documents")}</div>
+                    <ul>
+                        {attachments
+                            .into_iter()
+                            .map(|attachment| view! { cx,
+                                <li>
+                                    <a href=attachment.url download=attachment.name.clone() class="link link-info">
+                                        {attachment.name}
+                                    </a>
+                                    " ("{format_size(attachment.size)}")"
+                                </li>
+                            })
+                            .collect_view(cx)}
+                    </ul>
+                </div>
+            </div>
+        }
+    })
+}
@@ -0,0 +1,415 @@
+//! Translations of the UI into English and Traditional Chinese.
+//!
+//! Messages live in the Fluent catalogs under `locales/`, which are compiled into both
+//! the server and the WASM bundle. During SSR the locale is taken from the `lang` cookie
+//! set by the language switcher, else negotiated from `Accept-Language`, and written to
+//! `<html lang>`, where the client reads it back when hydrating. Switching the language
+//! reloads the page, so views may translate once instead of reactively.
+//!
+//! Times are shown in the configured `server.time_zone` rather than that of the server or
+//! the browser, so that both render the same text. It travels in `<html data-time-zone>`.
+
+use chrono::{DateTime, FixedOffset};
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::OnceLock};
+
+/// Cookie remembering the language picked in the switcher
+pub const LOCALE_COOKIE: &str = "lang";
+
+/// Attribute of `<html>` carrying the site time zone to the client
+pub const TIME_ZONE_ATTRIBUTE: &str = "data-time-zone";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    En,
+    ZhTw,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::ZhTw];
+
+    /// BCP 47 language tag, as used in `<html lang>` and the cookie
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhTw => "zh-TW",
+        }
+    }
+
+    /// Name of the language in the language itself, for the switcher
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::ZhTw => "繁體中文",
+        }
+    }
+
+    fn catalog(self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../locales/en.ftl"),
+            Locale::ZhTw => include_str!("../locales/zh-TW.ftl"),
+        }
+    }
+
+    fn bundle(self) -> &'static FluentBundle<FluentResource> {
+        static EN: OnceLock<FluentBundle<FluentResource>> = OnceLock::new();
+        static ZH_TW: OnceLock<FluentBundle<FluentResource>> = OnceLock::new();
+
+        let cell = match self {
+            Locale::En => &EN,
+            Locale::ZhTw => &ZH_TW,
+        };
+        cell.get_or_init(|| {
+            let lang = self.tag().parse().expect("locale tags should be valid");
+            let mut bundle = FluentBundle::new_concurrent(vec![lang]);
+            // the marks around arguments only matter for mixed text directions
+            bundle.set_use_isolating(false);
+            let resource = FluentResource::try_new(self.catalog().to_string())
+                .expect("message catalog should parse");
+            bundle
+                .add_resource(resource)
+                .expect("message catalog should not define a message twice");
+            bundle
+        })
+    }
+
+    /// Best supported match of an `Accept-Language` header, English if there is none
+    pub fn negotiate(accept_language: &str) -> Self {
+        let mut best = None::<(f32, Locale)>;
+        for range in accept_language.split(',') {
+            let mut parts = range.split(';');
+            let tag = parts.next().unwrap_or_default().trim();
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            let Ok(locale) = tag.parse::<Locale>() else {
+                continue;
+            };
+            if quality > 0.0 && best.map_or(true, |(q, _)| quality > q) {
+                best = Some((quality, locale));
+            }
+        }
+
+        best.map(|(_, locale)| locale).unwrap_or_default()
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// Any English or Chinese tag, e.g. `en-US` or `zh-Hant-TW`, Traditional Chinese is
+    /// closer to other Chinese variants than English is
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        let language = s.split(['-', '_']).next().unwrap_or_default();
+        match language {
+            "en" => Ok(Locale::En),
+            "zh" => Ok(Locale::ZhTw),
+            _ => Err(format!("unsupported locale {s:?}")),
+        }
+    }
+}
+
+/// `id` in the catalog of `locale`, falling back to English and then to `id` itself,
+/// so messages from the backend are shown as they are
+pub fn translate(locale: Locale, id: &str, args: Option<&FluentArgs>) -> String {
+    [locale, Locale::En]
+        .into_iter()
+        .find_map(|locale| {
+            let bundle = locale.bundle();
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = vec![];
+            Some(
+                bundle
+                    .format_pattern(pattern, args, &mut errors)
+                    .into_owned(),
+            )
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Translations in the locale of the current page, see [`use_i18n`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct I18n {
+    locale: Locale,
+    time_zone: FixedOffset,
+}
+
+impl Default for I18n {
+    fn default() -> Self {
+        Self::new(Locale::default())
+    }
+}
+
+impl I18n {
+    /// Translations showing times in UTC, see [`I18n::with_time_zone`]
+    pub fn new(locale: Locale) -> Self {
+        Self {
+            locale,
+            time_zone: FixedOffset::east_opt(0).unwrap(),
+        }
+    }
+
+    pub fn with_time_zone(self, time_zone: FixedOffset) -> Self {
+        Self { time_zone, ..self }
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// The site time zone, which times are shown and entered in
+    pub fn time_zone(&self) -> FixedOffset {
+        self.time_zone
+    }
+
+    pub fn t(&self, id: &str) -> String {
+        translate(self.locale, id, None)
+    }
+
+    /// Message `id` with variables, e.g. `i18n.t_args("topics-tagged", [("tag", tag.into())])`
+    pub fn t_args<'a>(
+        &self,
+        id: &str,
+        args: impl IntoIterator<Item = (&'a str, FluentValue<'a>)>,
+    ) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(name, value);
+        }
+        translate(self.locale, id, Some(&fluent_args))
+    }
+
+    /// An RFC 3339 time in the site time zone, as it is written in this locale
+    ///
+    /// Returned as it is if it cannot be parsed.
+    pub fn format_datetime(&self, time: &str) -> String {
+        let Ok(time) = DateTime::parse_from_rfc3339(time) else {
+            return time.to_string();
+        };
+        let format = match self.locale {
+            Locale::En => "%b %-d, %Y %H:%M",
+            Locale::ZhTw => "%Y年%-m月%-d日 %H:%M",
+        };
+
+        time.with_timezone(&self.time_zone)
+            .format(format)
+            .to_string()
+    }
+
+    /// A whole number with thousands separators
+    pub fn format_number(&self, number: usize) -> String {
+        let separator = match self.locale {
+            Locale::En | Locale::ZhTw => ',',
+        };
+        let digits = number.to_string();
+        let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                formatted.push(separator);
+            }
+            formatted.push(digit);
+        }
+
+        formatted
+    }
+
+    /// `ratio` between 0 and 1 as a percentage with two decimals
+    pub fn format_percent(&self, ratio: f64) -> String {
+        match self.locale {
+            Locale::En | Locale::ZhTw => format!("{:.2}%", ratio * 100.0),
+        }
+    }
+}
+
+/// Locale of the current request on the server, of `<html lang>` in the browser
+fn detect_locale(cx: Scope) -> Locale {
+    #[cfg(feature = "ssr")]
+    {
+        use actix_web::{http::header, HttpRequest};
+
+        let Some(req) = use_context::<HttpRequest>(cx) else {
+            return Locale::default();
+        };
+        if let Some(locale) = req
+            .cookie(LOCALE_COOKIE)
+            .and_then(|cookie| cookie.value().parse().ok())
+        {
+            return locale;
+        }
+        req.headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .map(Locale::negotiate)
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        _ = cx;
+        // rendered by the server, else e.g. under `trunk serve` the browser language
+        document()
+            .document_element()
+            .and_then(|html| html.get_attribute("lang"))
+            .or_else(|| window().navigator().language())
+            .and_then(|lang| lang.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// The configured site time zone on the server, that of `<html data-time-zone>` in the
+/// browser, UTC if there is none
+fn detect_time_zone() -> FixedOffset {
+    #[cfg(feature = "ssr")]
+    {
+        crate::server::config::config().server.time_zone()
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        document()
+            .document_element()
+            .and_then(|html| html.get_attribute(TIME_ZONE_ATTRIBUTE))
+            .and_then(|offset| offset.parse().ok())
+            .unwrap_or(FixedOffset::east_opt(0).unwrap())
+    }
+}
+
+/// Pick the locale of this page and make it available to [`use_i18n`]
+pub fn provide_i18n(cx: Scope) -> I18n {
+    let i18n = I18n::new(detect_locale(cx)).with_time_zone(detect_time_zone());
+    provide_context(cx, i18n);
+
+    i18n
+}
+
+pub fn use_i18n(cx: Scope) -> I18n {
+    use_context::<I18n>(cx).unwrap_or_default()
+}
+
+/// Remember `locale` in the cookie and render the page again in it
+///
+/// Only works in the browser.
+pub fn switch_locale(locale: Locale) {
+    use wasm_bindgen::JsCast;
+
+    let cookie = format!(
+        "{LOCALE_COOKIE}={}; path=/; max-age=31536000; samesite=lax",
+        locale.tag()
+    );
+    if let Ok(document) = document().dyn_into::<web_sys::HtmlDocument>() {
+        _ = document.set_cookie(&cookie);
+    }
+    _ = window().location().reload();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn negotiates_the_preferred_supported_locale() {
+        assert_eq!(Locale::negotiate("zh-TW,zh;q=0.9,en;q=0.8"), Locale::ZhTw);
+        assert_eq!(
+            Locale::negotiate("fr;q=1.0, en-GB;q=0.5, zh;q=0.7"),
+            Locale::ZhTw
+        );
+        assert_eq!(Locale::negotiate("en;q=0.3, zh-CN;q=0.2"), Locale::En);
+        // q=0 means not acceptable
+        assert_eq!(Locale::negotiate("zh;q=0, fr"), Locale::En);
+        assert_eq!(Locale::negotiate("de, fr;q=0.5"), Locale::En);
+        assert_eq!(Locale::negotiate(""), Locale::En);
+    }
+
+    #[test]
+    fn parses_english_and_chinese_tags() {
+        assert_eq!("en".parse(), Ok(Locale::En));
+        assert_eq!("en-US".parse(), Ok(Locale::En));
+        assert_eq!("zh-TW".parse(), Ok(Locale::ZhTw));
+        assert_eq!("zh-Hant-TW".parse(), Ok(Locale::ZhTw));
+        assert_eq!("zh_CN".parse(), Ok(Locale::ZhTw));
+        assert!("fr".parse::<Locale>().is_err());
+        assert!("".parse::<Locale>().is_err());
+        for locale in Locale::ALL {
+            assert_eq!(locale.tag().parse(), Ok(locale));
+        }
+    }
+
+    #[test]
+    fn formats_numbers_with_thousands_separators() {
+        let i18n = I18n::new(Locale::En);
+        assert_eq!(i18n.format_number(0), "0");
+        assert_eq!(i18n.format_number(999), "999");
+        assert_eq!(i18n.format_number(1000), "1,000");
+        assert_eq!(i18n.format_number(1234567), "1,234,567");
+        assert_eq!(I18n::new(Locale::ZhTw).format_number(12345), "12,345");
+    }
+
+    #[test]
+    fn formats_times_in_the_site_time_zone() {
+        let taipei = FixedOffset::east_opt(8 * 3600).unwrap();
+        let time = "2024-09-16T17:30:00Z";
+        assert_eq!(
+            I18n::new(Locale::En).format_datetime(time),
+            "Sep 16, 2024 17:30"
+        );
+        assert_eq!(
+            I18n::new(Locale::En)
+                .with_time_zone(taipei)
+                .format_datetime(time),
+            "Sep 17, 2024 01:30"
+        );
+        assert_eq!(
+            I18n::new(Locale::ZhTw)
+                .with_time_zone(taipei)
+                .format_datetime(time),
+            "2024年9月17日 01:30"
+        );
+        assert_eq!(I18n::new(Locale::En).format_datetime("soon"), "soon");
+    }
+
+    #[test]
+    fn falls_back_to_the_message_id() {
+        assert_eq!(
+            translate(Locale::ZhTw, "no such message", None),
+            "no such message"
+        );
+    }
+
+    fn message_ids(catalog: &str) -> BTreeSet<&str> {
+        catalog
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| line.split_once('='))
+            .map(|(id, _)| id.trim())
+            .collect()
+    }
+
+    #[test]
+    fn every_locale_has_every_message() {
+        let en = message_ids(Locale::En.catalog());
+        assert!(en.contains("site-title"));
+        for locale in Locale::ALL {
+            let ids = message_ids(locale.catalog());
+            assert_eq!(
+                en.difference(&ids).collect::<Vec<_>>(),
+                Vec::<&&str>::new(),
+                "missing from {}",
+                locale.tag()
+            );
+            assert_eq!(
+                ids.difference(&en).collect::<Vec<_>>(),
+                Vec::<&&str>::new(),
+                "only in {}",
+                locale.tag()
+            );
+            // and every catalog parses
+            locale.bundle();
+        }
+    }
+}
@@ -1,8 +1,13 @@
 pub mod api;
 pub mod app;
 pub mod component;
+pub mod i18n;
 pub mod page;
+#[cfg(feature = "ssr")]
+pub mod server;
 pub mod state;
+pub mod uri;
+pub mod validate;
 use cfg_if::cfg_if;
 
 cfg_if! {
@@ -3,37 +3,143 @@
 async fn main() -> std::io::Result<()> {
     use actix_files::Files;
     use actix_web::*;
+    use clap::Parser;
     use leptos::*;
     use leptos_actix::{generate_route_list, LeptosRoutes};
     use vote::api::{
-        CreateAccessToken, CreateComment, CreateTopic, CreateVote, GetComments, GetMe, GetMyVote,
-        GetOneTopic, GetTopics, GetVoteResult, Signup,
+        ChangePassword, CheckAvailability, CreateAccessToken, CreateComment, CreateTemplate,
+        CreateTopic, CreateVote, DeleteTemplate, GetComments, GetCooldown, GetDrafts, GetMe,
+        GetMyComments, GetMyTopics, GetMyVote, GetMyVotes, GetOneTopic, GetReactions, GetReports,
+        GetTags, GetTemplates, GetTopics, GetVoteResult, MergeTags, PublishTopic, RenameTag,
+        ReportComment, RequestEmailVerification, RequestPasswordReset, ResetPassword,
+        ResolveReport, SetReaction, Signup, SsoEnabled, UpdateTopic, VerifyEmail,
     };
 
     use vote::app::*;
+    use vote::server::{
+        assets,
+        config::{self, Args, Config},
+        csrf::OriginCheck,
+        mail,
+        metrics::RequestMetrics,
+        rate_limit::{RateLimit, RateLimiter},
+        security::{self, ScriptNonce},
+        shutdown::{self, TrackInFlight},
+        telemetry::{self, RequestId},
+    };
+
+    let args = Args::parse();
+    let config = match Config::load(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: invalid configuration: {e:#}");
+            std::process::exit(2);
+        }
+    };
+    if args.print_config {
+        print!("{}", config.to_redacted_toml());
+        return Ok(());
+    }
+    telemetry::init(&config.log);
+    if let Err(e) = mail::init(&config.mail) {
+        eprintln!("error: invalid mail settings: {e:#}");
+        std::process::exit(2);
+    }
+    config::init(config);
+    let config = config::config();
 
-    let conf = get_configuration(None).await.unwrap();
-    let addr = conf.leptos_options.site_addr;
+    let mut conf = get_configuration(None).await.unwrap();
+    if let Err(e) = assets::init(&mut conf.leptos_options) {
+        eprintln!("error: invalid assets: {e}");
+        std::process::exit(2);
+    }
+    let addr = config.server.bind.unwrap_or(conf.leptos_options.site_addr);
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(|cx| view! { cx, <App/> });
 
-    _ = CreateTopic::register();
-    _ = GetOneTopic::register();
-    _ = GetTopics::register();
-    _ = CreateAccessToken::register();
-    _ = Signup::register();
-    _ = GetMe::register();
-    _ = CreateVote::register();
-    _ = GetComments::register();
-    _ = CreateComment::register();
-    _ = GetMyVote::register();
-    _ = GetVoteResult::register();
+    // every server fn, those that change state must be called from this site
+    let mut mutating = Vec::new();
+    macro_rules! register {
+        (reads: [$($read:ident),* $(,)?], writes: [$($write:ident),* $(,)?] $(,)?) => {
+            $(_ = $read::register();)*
+            $(
+                _ = $write::register();
+                mutating.push(format!("/api/{}", $write::url()));
+            )*
+        };
+    }
+    register! {
+        reads: [
+            GetOneTopic,
+            GetTopics,
+            GetMe,
+            GetComments,
+            GetMyVote,
+            GetVoteResult,
+            GetReactions,
+            GetReports,
+            GetMyTopics,
+            GetMyVotes,
+            GetMyComments,
+            CheckAvailability,
+            SsoEnabled,
+            GetCooldown,
+            GetTags,
+            GetDrafts,
+            GetTemplates,
+        ],
+        writes: [
+            CreateTopic,
+            CreateVote,
+            CreateComment,
+            Signup,
+            SetReaction,
+            ReportComment,
+            ResolveReport,
+            ChangePassword,
+            RequestPasswordReset,
+            ResetPassword,
+            RequestEmailVerification,
+            VerifyEmail,
+            CreateAccessToken,
+            RenameTag,
+            MergeTags,
+            UpdateTopic,
+            PublishTopic,
+            CreateTemplate,
+            DeleteTemplate,
+        ],
+    }
+
+    // "Login with school account" is only offered if an OpenID Connect provider is configured
+    // an unreachable provider should not take password logins down with it
+    let oidc = vote::server::oidc::Oidc::from_config(config)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("login with school account disabled: {e:#}");
+            None
+        })
+        .map(web::Data::new);
+
+    let limiter = std::sync::Arc::new(RateLimiter::with_overrides(&config.rate_limits));
+    let csp = security::content_security_policy(config, &conf.leptos_options);
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         let leptos_options = &conf.leptos_options;
         let site_root = &leptos_options.site_root;
 
         App::new()
+            .app_data(web::Data::from(limiter.clone()))
+            .configure(vote::server::health::routes)
+            .configure(vote::server::metrics::routes)
+            .configure(|cfg| {
+                if let Some(oidc) = &oidc {
+                    cfg.app_data(oidc.clone());
+                    vote::server::oidc::routes(cfg);
+                }
+            })
+            .configure(assets::routes)
+            .configure(vote::server::upload::routes)
             .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
             .leptos_routes(
                 leptos_options.to_owned(),
@@ -41,11 +147,44 @@ async fn main() -> std::io::Result<()> {
                 |cx| view! { cx, <App/> },
             )
             .service(Files::new("/", site_root))
-        //.wrap(middleware::Compress::default())
+            .wrap(middleware::Condition::new(
+                config.features.rate_limit,
+                RateLimit::new(limiter.clone()),
+            ))
+            .wrap(OriginCheck::new(mutating.clone()))
+            .wrap(security::headers(config))
+            .wrap(ScriptNonce::new(csp.clone()))
+            .wrap(assets::cache_headers())
+            .wrap(TrackInFlight)
+            .wrap(RequestMetrics)
+            .wrap(RequestId)
+            .wrap(middleware::Compress::default())
     })
-    .bind(&addr)?
-    .run()
-    .await
+    .client_request_timeout(std::time::Duration::from_secs(
+        config.server.request_timeout_secs,
+    ))
+    // SIGTERM is handled by `shutdown::on_signal`, which flips `/readyz` first
+    .disable_signals()
+    .shutdown_timeout(config.server.shutdown_grace_secs);
+
+    tracing::info!("listening on {addr}");
+    let server = match &config.server.tls {
+        Some(tls) => {
+            use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
+
+            let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+            acceptor.set_private_key_file(&tls.key, SslFiletype::PEM)?;
+            acceptor.set_certificate_chain_file(&tls.cert)?;
+            server.bind_openssl(addr, acceptor)?.run()
+        }
+        None => server.bind(addr)?.run(),
+    };
+    rt::spawn(shutdown::on_signal(server.handle(), &config.server));
+    rt::spawn(vote::server::upload::sweep_orphans());
+    server.await?;
+    shutdown::report();
+
+    Ok(())
 }
 
 #[cfg(not(any(feature = "ssr", feature = "csr")))]
@@ -0,0 +1,281 @@
+use crate::api::{
+    verify_email, ChangePassword, ChangePasswordInput, EmailInput, RequestEmailVerification,
+    RequestPasswordReset, ResetPassword, ResetPasswordInput, VerifyEmailInput,
+};
+use crate::component::*;
+use crate::i18n::use_i18n;
+use crate::state::GlobalState;
+use leptos::ev::SubmitEvent;
+use leptos::*;
+use leptos_router::*;
+
+const INPUT_STYLE: &str = "input input-bordered input-info w-full max-w-md";
+
+#[component]
+pub fn ChangePasswordPage(cx: Scope) -> impl IntoView {
+    use leptos::html::Input;
+
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let current_password: NodeRef<Input> = create_node_ref(cx);
+    let new_password: NodeRef<Input> = create_node_ref(cx);
+    let confirm_password: NodeRef<Input> = create_node_ref(cx);
+    let (mismatch, set_mismatch) = create_signal(cx, false);
+    let change_password = create_server_action::<ChangePassword>(cx);
+    let change_password_pending = change_password.pending();
+    let change_password_result = change_password.value();
+
+    let on_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+
+        if change_password_pending() {
+            return;
+        }
+
+        let current_password = current_password().expect("<input> to exist").value();
+        let new_password = new_password().expect("<input> to exist").value();
+        let confirm_password = confirm_password().expect("<input> to exist").value();
+        set_mismatch(new_password != confirm_password);
+        if mismatch() {
+            return;
+        }
+        let Some(token) = state().token().map(|t| t.to_string()) else {
+            return;
+        };
+
+        change_password.dispatch(ChangePassword {
+            token,
+            input: ChangePasswordInput {
+                current_password,
+                new_password,
+            },
+        });
+    };
+
+    view! { cx,
+        <div class="max-w-md mx-auto mt-8">
+            <div class="rounded-lg shadow-md p-8">
+                <h2 class="text-2xl font-semibold mb-6">{i18n.t("account-change-password")}</h2>
+                <form on:submit=on_submit>
+                    <div class="mb-4">
+                        <label for="current-password">
+                            <span class="label-text">{i18n.t("account-current-password")}</span>
+                        </label>
+                        <input id="current-password" type="password" node_ref=current_password class=INPUT_STYLE required />
+                    </div>
+                    <div class="mb-4">
+                        <label for="new-password">
+                            <span class="label-text">{i18n.t("account-new-password")}</span>
+                        </label>
+                        <input id="new-password" type="password" node_ref=new_password class=INPUT_STYLE required />
+                    </div>
+                    <div class="mb-6">
+                        <label for="confirm-password">
+                            <span class="label-text">{i18n.t("account-confirm-new-password")}</span>
+                        </label>
+                        <input id="confirm-password" type="password" node_ref=confirm_password class=INPUT_STYLE required />
+                        {move || mismatch().then(|| view! { cx,
+                            <span class="text-error text-sm">{i18n.t("password-mismatch")}</span>
+                        })}
+                    </div>
+                    <button type="submit" class="btn btn-primary py-2 px-4 w-full">
+                        {move || if change_password_pending() { i18n.t("loading") } else { i18n.t("account-change-password") }}
+                    </button>
+                </form>
+            </div>
+        </div>
+
+        <ErrorList error_title=i18n.t("account-change-password-failed")>
+            {move || change_password_result().map(|r| r.map(|_| view! { cx,
+                <div class="alert alert-success max-w-md mx-auto mt-4">{i18n.t("account-password-changed")}</div>
+            }))}
+        </ErrorList>
+    }
+}
+
+#[component]
+pub fn ForgotPasswordPage(cx: Scope) -> impl IntoView {
+    use leptos::html::Input;
+
+    let i18n = use_i18n(cx);
+    let email: NodeRef<Input> = create_node_ref(cx);
+    let request_reset = create_server_action::<RequestPasswordReset>(cx);
+    let request_reset_pending = request_reset.pending();
+    let request_reset_result = request_reset.value();
+
+    let on_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+
+        if request_reset_pending() {
+            return;
+        }
+
+        let email = email().expect("<input> to exist").value();
+        request_reset.dispatch(RequestPasswordReset {
+            input: EmailInput { email },
+        });
+    };
+
+    view! { cx,
+        <div class="max-w-md mx-auto mt-8">
+            <div class="rounded-lg shadow-md p-8">
+                <h2 class="text-2xl font-semibold mb-6">{i18n.t("account-forgot-password")}</h2>
+                <form on:submit=on_submit>
+                    <div class="mb-6">
+                        <label for="email">
+                            <span class="label-text">{i18n.t("email")}</span>
+                        </label>
+                        <input id="email" type="email" node_ref=email class=INPUT_STYLE required />
+                    </div>
+                    <button type="submit" class="btn btn-primary py-2 px-4 w-full">
+                        {move || if request_reset_pending() { i18n.t("loading") } else { i18n.t("account-send-reset-link") }}
+                    </button>
+                </form>
+            </div>
+        </div>
+
+        <ErrorList error_title=i18n.t("account-request-reset-failed")>
+            {move || request_reset_result().map(|r| r.map(|_| view! { cx,
+                <div class="alert alert-success max-w-md mx-auto mt-4">
+                    {i18n.t("account-reset-link-sent")}
+                </div>
+            }))}
+        </ErrorList>
+    }
+}
+
+#[component]
+pub fn ResetPasswordPage(cx: Scope) -> impl IntoView {
+    use leptos::html::Input;
+
+    let i18n = use_i18n(cx);
+    let query = use_query_map(cx);
+    let token = move || query.with(|q| q.get("token").cloned().unwrap_or_default());
+    let new_password: NodeRef<Input> = create_node_ref(cx);
+    let confirm_password: NodeRef<Input> = create_node_ref(cx);
+    let (mismatch, set_mismatch) = create_signal(cx, false);
+    let reset_password = create_server_action::<ResetPassword>(cx);
+    let reset_password_pending = reset_password.pending();
+    let reset_password_result = reset_password.value();
+
+    let on_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+
+        if reset_password_pending() {
+            return;
+        }
+
+        let new_password = new_password().expect("<input> to exist").value();
+        let confirm_password = confirm_password().expect("<input> to exist").value();
+        set_mismatch(new_password != confirm_password);
+        if mismatch() {
+            return;
+        }
+
+        reset_password.dispatch(ResetPassword {
+            input: ResetPasswordInput {
+                token: token(),
+                new_password,
+            },
+        });
+    };
+
+    view! { cx,
+        <div class="max-w-md mx-auto mt-8">
+            <div class="rounded-lg shadow-md p-8">
+                <h2 class="text-2xl font-semibold mb-6">{i18n.t("account-reset-password")}</h2>
+                <form on:submit=on_submit>
+                    <div class="mb-4">
+                        <label for="new-password">
+                            <span class="label-text">{i18n.t("account-new-password")}</span>
+                        </label>
+                        <input id="new-password" type="password" node_ref=new_password class=INPUT_STYLE required />
+                    </div>
+                    <div class="mb-6">
+                        <label for="confirm-password">
+                            <span class="label-text">{i18n.t("account-confirm-new-password")}</span>
+                        </label>
+                        <input id="confirm-password" type="password" node_ref=confirm_password class=INPUT_STYLE required />
+                        {move || mismatch().then(|| view! { cx,
+                            <span class="text-error text-sm">{i18n.t("password-mismatch")}</span>
+                        })}
+                    </div>
+                    <button type="submit" class="btn btn-primary py-2 px-4 w-full">
+                        {move || if reset_password_pending() { i18n.t("loading") } else { i18n.t("account-reset-password") }}
+                    </button>
+                </form>
+            </div>
+        </div>
+
+        <ErrorList error_title=i18n.t("account-reset-password-failed")>
+            {move || reset_password_result().map(|r| r.map(|_| view! { cx,
+                <div class="alert alert-success max-w-md mx-auto mt-4">
+                    {i18n.t("account-password-reset")}" "
+                    <a href="/login" class="link">{i18n.t("login")}</a>
+                </div>
+            }))}
+        </ErrorList>
+    }
+}
+
+#[component]
+pub fn VerifyEmailPage(cx: Scope) -> impl IntoView {
+    use leptos::html::Input;
+
+    let i18n = use_i18n(cx);
+    let query = use_query_map(cx);
+    let token = move || query.with(|q| q.get("token").cloned());
+    let verified = create_local_resource(cx, token, |token| async move {
+        match token {
+            Some(token) => Some(verify_email(VerifyEmailInput { token }).await),
+            None => None,
+        }
+    });
+
+    let email: NodeRef<Input> = create_node_ref(cx);
+    let resend = create_server_action::<RequestEmailVerification>(cx);
+    let resend_pending = resend.pending();
+    let resend_result = resend.value();
+    let on_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+
+        if resend_pending() {
+            return;
+        }
+
+        let email = email().expect("<input> to exist").value();
+        resend.dispatch(RequestEmailVerification {
+            input: EmailInput { email },
+        });
+    };
+
+    view! { cx,
+        <div class="max-w-md mx-auto mt-8">
+            <div class="rounded-lg shadow-md p-8">
+                <h2 class="text-2xl font-semibold mb-6">{i18n.t("account-verify-email")}</h2>
+                <Transition fallback=move || view! { cx, <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p> }>
+                    <ErrorList error_title=i18n.t("account-verify-email-failed")>
+                        {move || verified.read(cx).flatten().map(|r| r.map(|_| view! { cx,
+                            <div class="alert alert-success">{i18n.t("account-email-verified")}</div>
+                        }))}
+                    </ErrorList>
+                </Transition>
+                <form on:submit=on_submit class="mt-6">
+                    <label for="email">
+                        <span class="label-text">{i18n.t("account-resend-prompt")}</span>
+                    </label>
+                    <input id="email" type="email" node_ref=email class=INPUT_STYLE required />
+                    <button type="submit" class="btn btn-info py-2 px-4 w-full mt-4">
+                        {move || if resend_pending() { i18n.t("loading") } else { i18n.t("account-resend-verification") }}
+                    </button>
+                </form>
+            </div>
+        </div>
+
+        <ErrorList error_title=i18n.t("account-resend-failed")>
+            {move || resend_result().map(|r| r.map(|_| view! { cx,
+                <div class="alert alert-success max-w-md mx-auto mt-4">{i18n.t("account-verification-sent")}</div>
+            }))}
+        </ErrorList>
+    }
+}
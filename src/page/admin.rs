@@ -0,0 +1,256 @@
+use crate::api::{
+    get_reports, get_tags, MergeTags, MergeTagsInput, RenameTag, RenameTagInput, ReportAction,
+    ResolveReport, ResolveReportInput,
+};
+use crate::component::*;
+use crate::i18n::use_i18n;
+use crate::state::GlobalState;
+use leptos::*;
+
+/// Lists reported comments so that moderators can keep or remove them
+#[component]
+pub fn ModerationPage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let resolve_report = create_server_action::<ResolveReport>(cx);
+    let resolve_pending = resolve_report.pending();
+    let resolve_result = resolve_report.value();
+    let resolve_version = resolve_report.version();
+    let reports = create_local_resource(
+        cx,
+        move || (state().token().map(|t| t.to_string()), resolve_version()),
+        |(token, _)| async move {
+            match token {
+                Some(token) => Some(get_reports(token).await),
+                None => None,
+            }
+        },
+    );
+
+    let resolve = move |report_id: String, action: ReportAction| {
+        if resolve_pending() {
+            return;
+        }
+        let Some(token) = state().token().map(|t| t.to_string()) else {
+            return;
+        };
+        resolve_report.dispatch(ResolveReport {
+            token,
+            input: ResolveReportInput { report_id, action },
+        });
+    };
+
+    let reports_view = move || {
+        reports.read(cx).map(|reports| match reports {
+            None => Ok(view! { cx,
+                <p class="p-8 w-full text-center">{i18n.t("moderation-login")}</p>
+            }
+            .into_view(cx)),
+            Some(reports) => reports.map(|reports| {
+                if reports.is_empty() {
+                    return view! { cx,
+                        <p class="p-8 w-full text-center">{i18n.t("moderation-empty")}</p>
+                    }
+                    .into_view(cx);
+                }
+
+                reports
+                    .into_iter()
+                    .map(|report| {
+                        let keep_id = report.id.clone();
+                        let remove_id = report.id.clone();
+                        view! { cx,
+                            <div class="card bg-base-200 mb-4 shadow-xl">
+                                <div class="card-body">
+                                    <p class="text-lg">{report.comment.content}</p>
+                                    <p class="text-sm">
+                                        {i18n.t("moderation-reason")}{report.reason} <br />
+                                        {i18n.t("moderation-reported-by")}{report.reporter} <br />
+                                        {i18n.t("moderation-reported-at")}{i18n.format_datetime(&report.created_at)}
+                                    </p>
+                                    <div class="card-actions justify-end">
+                                        <button
+                                            class="btn btn-success"
+                                            class:btn-disabled=resolve_pending
+                                            on:click=move |_| resolve(keep_id.clone(), ReportAction::Keep)
+                                        >
+                                            {i18n.t("moderation-keep")}
+                                        </button>
+                                        <button
+                                            class="btn btn-error"
+                                            class:btn-disabled=resolve_pending
+                                            on:click=move |_| resolve(remove_id.clone(), ReportAction::Remove)
+                                        >
+                                            {i18n.t("moderation-remove")}
+                                        </button>
+                                    </div>
+                                </div>
+                            </div>
+                        }
+                    })
+                    .collect_view(cx)
+            }),
+        })
+    };
+
+    view! { cx,
+        <div class="p-4 max-w-3xl mx-auto">
+            <h2 class="text-3xl pb-4">{i18n.t("moderation-title")}</h2>
+            <Transition fallback=move || view! { cx, <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p> }>
+                <ErrorList error_title=i18n.t("moderation-title")>
+                    {reports_view}
+                </ErrorList>
+            </Transition>
+            <ErrorList error_title=i18n.t("moderation-resolve-failed")>
+                {move || resolve_result().map(|r| r.map(|_| ()))}
+            </ErrorList>
+        </div>
+    }
+}
+
+/// Lists the tags in use so that admins can rename them or merge several into one
+#[component]
+pub fn TagsPage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let rename_tag = create_server_action::<RenameTag>(cx);
+    let merge_tags = create_server_action::<MergeTags>(cx);
+    let pending = move || rename_tag.pending()() || merge_tags.pending()();
+    let tags = create_resource(
+        cx,
+        move || (rename_tag.version()(), merge_tags.version()()),
+        |_| get_tags(),
+    );
+    // tags checked for merging
+    let selected = create_rw_signal(cx, Vec::<String>::new());
+    let (merge_into, set_merge_into) = create_signal(cx, String::new());
+
+    let token = move || state().token().map(|t| t.to_string());
+    let rename = move |tag: String, new_name: String| {
+        if pending() {
+            return;
+        }
+        let Some(token) = token() else {
+            return;
+        };
+        rename_tag.dispatch(RenameTag {
+            token,
+            input: RenameTagInput { tag, new_name },
+        });
+    };
+    let merge = move |_| {
+        if pending() {
+            return;
+        }
+        let Some(token) = token() else {
+            return;
+        };
+        merge_tags.dispatch(MergeTags {
+            token,
+            input: MergeTagsInput {
+                tags: selected(),
+                into: merge_into(),
+            },
+        });
+        selected.set(vec![]);
+    };
+
+    let tags_view = move || {
+        tags.read(cx).map(|tags| {
+            tags.map(|tags| {
+                if tags.is_empty() {
+                    return view! { cx, <p class="p-8 w-full text-center">{i18n.t("tags-empty")}</p> }
+                        .into_view(cx);
+                }
+
+                tags.into_iter()
+                    .map(|tag| {
+                        let (new_name, set_new_name) = create_signal(cx, tag.name.clone());
+                        let name = store_value(cx, tag.name.clone());
+                        let toggle = move |_| {
+                            let name = name.get_value();
+                            selected.update(|s| {
+                                if s.contains(&name) {
+                                    s.retain(|t| *t != name);
+                                } else {
+                                    s.push(name);
+                                }
+                            })
+                        };
+                        view! { cx,
+                            <tr>
+                                <td>
+                                    <input
+                                        type="checkbox"
+                                        class="checkbox"
+                                        aria-label=i18n.t_args("tags-select", [("tag", tag.name.as_str().into())])
+                                        prop:checked=move || selected.with(|s| name.with_value(|n| s.contains(n)))
+                                        on:change=toggle
+                                    />
+                                </td>
+                                <td>
+                                    <a href=tag_href(&tag.name) class="link">{tag.name.clone()}</a>
+                                </td>
+                                <td>{tag.topics}</td>
+                                <td class="join">
+                                    <input
+                                        type="text"
+                                        class="input input-bordered input-sm join-item"
+                                        aria-label=i18n.t_args("tags-new-name", [("tag", tag.name.as_str().into())])
+                                        prop:value=new_name
+                                        on:input=move |ev| set_new_name(event_target_value(&ev))
+                                    />
+                                    <button
+                                        class="btn btn-sm join-item"
+                                        class:btn-disabled=pending
+                                        on:click=move |_| rename(name.get_value(), new_name())
+                                    >
+                                        {i18n.t("tags-rename")}
+                                    </button>
+                                </td>
+                            </tr>
+                        }
+                    })
+                    .collect_view(cx)
+            })
+        })
+    };
+
+    view! { cx,
+        <div class="p-4 max-w-3xl mx-auto">
+            <h2 class="text-3xl pb-4">{i18n.t("nav-tags")}</h2>
+            <div class="join mb-4">
+                <input
+                    type="text"
+                    class="input input-bordered join-item"
+                    placeholder=i18n.t("tags-merge-into")
+                    prop:value=merge_into
+                    on:input=move |ev| set_merge_into(event_target_value(&ev))
+                />
+                <button
+                    class="btn btn-primary join-item"
+                    class:btn-disabled={move || pending() || selected.with(Vec::is_empty)}
+                    on:click=merge
+                >
+                    {i18n.t("tags-merge")}
+                </button>
+            </div>
+            <Transition fallback=move || view! { cx, <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p> }>
+                <ErrorList error_title=i18n.t("nav-tags")>
+                    <table class="table">
+                        <thead>
+                            <tr><th></th><th>{i18n.t("tags-tag")}</th><th>{i18n.t("nav-topics")}</th><th>{i18n.t("tags-rename")}</th></tr>
+                        </thead>
+                        <tbody>{tags_view}</tbody>
+                    </table>
+                </ErrorList>
+            </Transition>
+            <ErrorList error_title=i18n.t("tags-rename-failed")>
+                {move || rename_tag.value()().map(|r| r.map(|_| ()))}
+            </ErrorList>
+            <ErrorList error_title=i18n.t("tags-merge-failed")>
+                {move || merge_tags.value()().map(|r| r.map(|_| ()))}
+            </ErrorList>
+        </div>
+    }
+}
@@ -1,54 +1,115 @@
+pub mod account;
+pub mod admin;
+pub mod profile;
 pub mod topic;
 
+pub use account::*;
+pub use admin::*;
+pub use profile::*;
 pub use topic::*;
 
 use leptos::ev::SubmitEvent;
+use leptos::leptos_dom::helpers::location_hash;
 use leptos::*;
 use leptos_router::*;
 
-use crate::api::{get_topics, CreateAccessToken, OAuth2PasswordRequest, Signup, SignupInput};
+use crate::api::{
+    check_availability, get_topics, sso_enabled, CheckAvailabilityInput, CreateAccessToken,
+    FieldErrors, GetTopicsInput, OAuth2PasswordRequest, PageInput, Role, Signup, SignupInput,
+    Stage, TopicQuery, TopicSort,
+};
 use crate::component::*;
+use crate::i18n::use_i18n;
 use crate::state::GlobalState;
+use crate::uri::encode_component;
+use crate::validate::{
+    password_strength, validate_email, validate_password, validate_username, PasswordStrength,
+};
 
-/// Renders the home page. Which contains the topic list
+/// Renders the home page, a dashboard of what is being voted on right now
 #[component]
 pub fn HomePage(cx: Scope) -> impl IntoView {
-    let loading =
-        move || view! { cx, <p>"Loading..." <span class="loading loading-spinner"></span></p> };
-    let topics = create_local_resource(cx, || (), |_| async move { get_topics().await });
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let is_organizer = move || state.with(|s| s.has_role(Role::Organizer));
+
+    view! { cx,
+        <div class="p-4 md:px-16">
+            <div class="flex items-center gap-4">
+                <h2 class="text-3xl font-semibold flex-1">{i18n.t("home-voting")}</h2>
+                {move || is_organizer().then(|| view! { cx,
+                    <a href="/topic/create" class="btn btn-primary">{i18n.t("nav-new-topic")}</a>
+                })}
+                <a href="/topics" class="btn btn-ghost">{i18n.t("home-all-topics")}</a>
+            </div>
+            <TopicShelf stage=Stage::Voting page_size=6 empty="home-voting-empty" />
+            <h2 class="text-2xl font-semibold mt-8">{i18n.t("home-upcoming")}</h2>
+            <TopicShelf stage=Stage::Upcoming page_size=3 empty="home-upcoming-empty" />
+        </div>
+    }
+}
+
+/// A few topics of one stage, closest end date first, or the message `empty` if there
+/// are none
+#[component]
+fn TopicShelf(cx: Scope, stage: Stage, page_size: usize, empty: &'static str) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let loading = move || {
+        view! { cx, <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p> }
+    };
+    let input = GetTopicsInput {
+        query: TopicQuery {
+            stages: vec![stage],
+            sort: TopicSort::Ends,
+            ..Default::default()
+        },
+        page: PageInput { page: 1, page_size },
+    };
+    let topics = create_resource(cx, move || input.clone(), get_topics);
     let topics = move || {
-        // FIXME: error handling
-        match topics.read(cx).map(|topics| topics.unwrap()) {
-            None => loading().into_view(cx),
-            Some(data) => data
-                .into_iter()
-                .map(|topic| {
-                    let (topic, _) = create_signal(cx, topic);
+        topics.read(cx).map(|topics| {
+            topics.map(|topics| {
+                if topics.items.is_empty() {
+                    return view! { cx, <p class="p-8 text-center">{i18n.t(empty)}</p> }
+                        .into_view(cx);
+                }
+                let more = (topics.total > topics.items.len()).then(|| {
+                    let href = topic_query_href(
+                        "/topics",
+                        &TopicQuery {
+                            stages: vec![stage],
+                            sort: TopicSort::Ends,
+                            ..Default::default()
+                        },
+                    );
                     view! { cx,
-                        <TopicCard topic=topic show_action=true />
+                        <a href=href class="link link-info w-full text-center">
+                            {i18n.t_args("home-all-topics-count", [("count", topics.total.into())])}
+                        </a>
                     }
-                    .into_view(cx)
-                })
-                .collect_view(cx),
-        }
+                });
+                let cards = topics
+                    .items
+                    .into_iter()
+                    .map(|topic| {
+                        let (topic, _) = create_signal(cx, topic);
+                        view! { cx, <TopicCard topic=topic show_action=true /> }
+                    })
+                    .collect_view(cx);
+
+                view! { cx, {cards} {more} }.into_view(cx)
+            })
+        })
     };
 
     view! { cx,
-        <div class="p-4">
-            <button
-                on:click=move |_| {
-                    let goto = use_navigate(cx);
-                    // FIXME: error handling
-                    let _ = goto("/topic/create", NavigateOptions::default());
-                }
-                class="btn btn-primary my-4"
-            >"New Topic"</button>
-            <Transition fallback=loading>
-                <div class="flex flex-col items-center w-full mx-auto">
+        <Transition fallback=loading>
+            <ErrorList error_title=i18n.t("nav-topics")>
+                <div class="flex flex-wrap justify-center gap-4 my-4">
                     {topics}
                 </div>
-            </Transition>
-        </div>
+            </ErrorList>
+        </Transition>
     }
 }
 
@@ -56,23 +117,68 @@ pub fn HomePage(cx: Scope) -> impl IntoView {
 pub fn LoginPage(cx: Scope) -> impl IntoView {
     use leptos::html::Input;
 
+    let i18n = use_i18n(cx);
     let username: NodeRef<Input> = create_node_ref(cx);
     let password: NodeRef<Input> = create_node_ref(cx);
     let goto = use_navigate(cx);
+    let query = use_query_map(cx);
+    let next = move || safe_next(query.with(|q| q.get("next").cloned()));
+    // set by the OpenID Connect callback when the school login failed, anything else in
+    // the link is not ours to show
+    let sso_error = move || {
+        query.with(|q| match q.get("error").map(String::as_str) {
+            Some("sso-failed") => Some(i18n.t("login-sso-failed")),
+            _ => None,
+        })
+    };
+    let sso_enabled = create_resource(cx, || (), move |_| sso_enabled(cx));
+    let sso_button = move || {
+        // without an answer only password logins are offered
+        let enabled = match sso_enabled.read(cx) {
+            Some(Ok(enabled)) => enabled,
+            Some(Err(e)) => {
+                error!("checking for login with school account failed: {e}");
+                false
+            }
+            None => false,
+        };
+        enabled.then(|| {
+            view! { cx,
+                <div class="divider">{i18n.t("login-or")}</div>
+                <a
+                    href=move || format!("/auth/oidc/login?next={}", encode_component(&next()))
+                    rel="external"
+                    class="btn btn-outline w-full"
+                >
+                    {i18n.t("login-sso")}
+                </a>
+            }
+        })
+    };
     let create_access_token = create_server_action::<CreateAccessToken>(cx);
     let login_pending = create_access_token.pending();
     let token = create_access_token.value();
     let state = expect_context::<RwSignal<GlobalState>>(cx);
 
+    // failed logins may be rate limited, tell how long to wait
+    let (cooldown, check_cooldown) = create_cooldown(cx, "create_access_token");
+    let (attempted, set_attempted) = create_signal(cx, None::<String>);
+    create_effect(cx, move |_| {
+        if let Some(Err(_)) = token() {
+            check_cooldown(attempted.get_untracked());
+        }
+    });
+
     let on_submit = move |ev: SubmitEvent| {
         ev.prevent_default();
 
-        if login_pending() {
+        if login_pending() || cooldown() > 0 {
             return;
         }
 
         let username = username().expect("<input> to exist").value();
         let password = password().expect("<input> to exist").value();
+        set_attempted(Some(username.clone()));
         let input = OAuth2PasswordRequest { username, password };
 
         create_access_token.dispatch(CreateAccessToken { input });
@@ -83,73 +189,190 @@ pub fn LoginPage(cx: Scope) -> impl IntoView {
     view! { cx,
         <div class="max-w-md mx-auto mt-8">
             <div class="rounded-lg shadow-md p-8">
-                <h2 class="text-2xl font-semibold mb-6">"Login"</h2>
+                <h2 class="text-2xl font-semibold mb-6">{i18n.t("login")}</h2>
                 <form on:submit=on_submit>
                     <div class="mb-4">
                         <label for="username">
-                            <div class="label-text">"Username / Email"</div>
+                            <div class="label-text">{i18n.t("login-username")}</div>
                         </label>
                         <input id="username" type="username" node_ref=username class=input_style required />
                     </div>
                     <div class="mb-6">
                         <label for="password">
-                            <div class="label-text">"Password"</div>
+                            <div class="label-text">{i18n.t("password")}</div>
                         </label>
                         <input id="password" type="password" node_ref=password class=input_style required />
                     </div>
+                    {move || sso_error().map(|e| view! { cx,
+                        <div class="alert alert-error mb-4">{e}</div>
+                    })}
+                    <CooldownAlert seconds=cooldown/>
                     <div>
-                        <button type="submit" class="btn btn-primary py-2 px-4 w-full">
+                        <button
+                            type="submit"
+                            class="btn btn-primary py-2 px-4 w-full"
+                            class:btn-disabled={move || cooldown() > 0}
+                        >
                             {move || if login_pending() {
                                 view! { cx, <span class="loading loading-spinner"></span> }.into_view(cx)
                             } else {
-                                view! { cx, "Login" }.into_view(cx)
+                                i18n.t("login").into_view(cx)
                             }}
                         </button>
                     </div>
                 </form>
+                {sso_button}
                 <div class="mt-4 text-center">
-                    <span>"Don't have an account? "</span>
-                    <a href="/signup" class="link link-info">"Signup"</a>
+                    <span>{i18n.t("login-no-account")}" "</span>
+                    <a href="/signup" class="link link-info">{i18n.t("signup")}</a>
+                </div>
+                <div class="mt-2 text-center">
+                    <a href="/forgot-password" class="link link-info">{i18n.t("login-forgot-password")}</a>
                 </div>
             </div>
         </div>
 
-        <ErrorList error_title="Login failed!".to_string()>
+        <ErrorList error_title=i18n.t("login-failed")>
             {move || token().map(|token| token.map(|token| {
                 state.update(|s| s.set_token(token.access_token));
                 // FIXME: error handling
-                goto("/", NavigateOptions::default())
+                goto(&next(), NavigateOptions::default())
             }))}
         </ErrorList>
     }
 }
 
+/// Receives the token from the OpenID Connect callback, see `server::oidc`
 #[component]
-pub fn SignupPage(cx: Scope) -> impl IntoView {
-    use leptos::html::Input;
+pub fn OidcCallbackPage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
 
-    let username: NodeRef<Input> = create_node_ref(cx);
-    let password: NodeRef<Input> = create_node_ref(cx);
-    let email: NodeRef<Input> = create_node_ref(cx);
+    // the token is passed in the URL fragment, so only the browser ever sees it
+    create_effect(cx, move |_| {
+        let hash = location_hash().unwrap_or_default();
+        let Ok(params) = web_sys::UrlSearchParams::new_with_str(&hash) else {
+            return;
+        };
+        let Some(token) = params.get("access_token") else {
+            return;
+        };
+        state.update(|s| s.set_token(token));
+        let goto = use_navigate(cx);
+        let options = NavigateOptions {
+            replace: true,
+            ..Default::default()
+        };
+        if let Err(e) = goto(&safe_next(params.get("next")), options) {
+            error!("leaving the login callback failed: {e}");
+        }
+    });
+
+    view! { cx,
+        <p class="p-8 text-center">{i18n.t("login-logging-in")} <span class="loading loading-spinner"></span></p>
+    }
+}
+
+/// Renders a validation message below a form field
+fn field_hint(cx: Scope, message: Option<String>) -> impl IntoView {
+    message.map(|message| view! { cx, <span class="text-error text-sm">{message}</span> })
+}
+
+#[component]
+pub fn SignupPage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let (username, set_username) = create_signal(cx, "".to_string());
+    let (email, set_email) = create_signal(cx, "".to_string());
+    let (password, set_password) = create_signal(cx, "".to_string());
+    let (confirm_password, set_confirm_password) = create_signal(cx, "".to_string());
     let signup = create_server_action::<Signup>(cx);
     let signup_result = signup.value();
     let signup_pending = signup.pending();
 
+    // ask the backend whether the username / email is taken once the user leaves the field
+    let (checked, set_checked) = create_signal(cx, CheckAvailabilityInput::default());
+    let availability = create_local_resource(cx, checked, |input| async move {
+        if input.username.is_none() && input.email.is_none() {
+            return None;
+        }
+        // the backend still refuses taken names on signup
+        check_availability(input)
+            .await
+            .map_err(|e| error!("checking availability failed: {e}"))
+            .ok()
+    });
+    let availability = move || availability.read(cx).flatten().unwrap_or_default();
+
+    let server_errors = move || {
+        signup_result()
+            .and_then(|r| r.err())
+            .and_then(|e| FieldErrors::from_error(&e))
+            .unwrap_or_default()
+    };
+    let username_error = move || {
+        let username = username();
+        if username.is_empty() {
+            return None;
+        }
+        validate_username(&username)
+            .err()
+            .map(|invalid| i18n.t(invalid.message_id()))
+            .or_else(|| {
+                (availability().username_taken && checked().username.as_ref() == Some(&username))
+                    .then(|| i18n.t("signup-username-taken"))
+            })
+            .or_else(|| server_errors().get("username").map(|id| i18n.t(id)))
+    };
+    let email_error = move || {
+        let email = email();
+        if email.is_empty() {
+            return None;
+        }
+        validate_email(&email)
+            .err()
+            .map(|invalid| i18n.t(invalid.message_id()))
+            .or_else(|| {
+                (availability().email_taken && checked().email.as_ref() == Some(&email))
+                    .then(|| i18n.t("signup-email-taken"))
+            })
+            .or_else(|| server_errors().get("email").map(|id| i18n.t(id)))
+    };
+    let password_error = move || {
+        let password = password();
+        if password.is_empty() {
+            return None;
+        }
+        validate_password(&password)
+            .err()
+            .map(|invalid| i18n.t(invalid.message_id()))
+            .or_else(|| server_errors().get("password").map(|id| i18n.t(id)))
+    };
+    let confirm_password_error = move || {
+        let confirm_password = confirm_password();
+        (!confirm_password.is_empty() && confirm_password != password())
+            .then(|| i18n.t("password-mismatch"))
+    };
+    let form_error = move || server_errors().get("form").map(|id| i18n.t(id));
+    let strength = move || password_strength(&password());
+
     let on_submit = move |ev: SubmitEvent| {
         ev.prevent_default();
 
         if signup_pending() {
             return;
         }
-
-        let username = username().expect("<input> to exist").value();
-        let password = password().expect("<input> to exist").value();
-        let email = email().expect("<input> to exist").value();
+        if username_error().is_some()
+            || email_error().is_some()
+            || password_error().is_some()
+            || confirm_password() != password()
+        {
+            return;
+        }
 
         let input = SignupInput {
-            username,
-            password,
-            email,
+            username: username(),
+            password: password(),
+            email: email(),
         };
 
         signup.dispatch(Signup { input });
@@ -161,52 +384,128 @@ pub fn SignupPage(cx: Scope) -> impl IntoView {
     view! { cx,
         <div class="max-w-md mx-auto mt-8">
             <div class="rounded-lg shadow-md p-8">
-                <h2 class="text-2xl font-semibold mb-6">"Signup"</h2>
+                <h2 class="text-2xl font-semibold mb-6">{i18n.t("signup")}</h2>
                 <form on:submit=on_submit>
                     <div class="mb-4">
                         <label for="username" class=label_style>
-                            <span class="label-text">"Username"</span>
+                            <span class="label-text">{i18n.t("username")}</span>
                         </label>
-                        <input type="username" node_ref=username class=input_style required />
+                        <input
+                            id="username"
+                            type="username"
+                            class=input_style
+                            class:input-error=move || username_error().is_some()
+                            on:input=move |ev| set_username(event_target_value(&ev))
+                            on:change=move |_| {
+                                if validate_username(&username()).is_ok() {
+                                    set_checked.update(|c| c.username = Some(username()));
+                                }
+                            }
+                            prop:value=username
+                            required
+                        />
+                        {move || field_hint(cx, username_error())}
                     </div>
                     <div class="mb-6">
                         <label for="email" class=label_style>
-                            <span class="label-text">"Email"</span>
+                            <span class="label-text">{i18n.t("email")}</span>
                         </label>
-                        <input type="email" node_ref=email class=input_style required />
+                        <input
+                            id="email"
+                            type="email"
+                            class=input_style
+                            class:input-error=move || email_error().is_some()
+                            on:input=move |ev| set_email(event_target_value(&ev))
+                            on:change=move |_| {
+                                if validate_email(&email()).is_ok() {
+                                    set_checked.update(|c| c.email = Some(email()));
+                                }
+                            }
+                            prop:value=email
+                            required
+                        />
+                        {move || field_hint(cx, email_error())}
                     </div>
                     <div class="mb-6">
                         <label for="password" class=label_style>
-                            <span class="label-text">"Password"</span>
+                            <span class="label-text">{i18n.t("password")}</span>
+                        </label>
+                        <input
+                            id="password"
+                            type="password"
+                            class=input_style
+                            class:input-error=move || password_error().is_some()
+                            on:input=move |ev| set_password(event_target_value(&ev))
+                            prop:value=password
+                            required
+                        />
+                        {move || (!password().is_empty()).then(|| view! { cx,
+                            <div class="flex items-center gap-2 mt-2">
+                                <progress
+                                    class="progress w-full"
+                                    class:progress-error=move || strength() == PasswordStrength::Weak
+                                    class:progress-warning=move || strength() == PasswordStrength::Fair
+                                    class:progress-info=move || strength() == PasswordStrength::Good
+                                    class:progress-success=move || strength() == PasswordStrength::Strong
+                                    value=move || strength().score()
+                                    max=4
+                                ></progress>
+                                <span class="text-sm">{move || i18n.t(strength().message_id())}</span>
+                            </div>
+                        })}
+                        {move || field_hint(cx, password_error())}
+                    </div>
+                    <div class="mb-6">
+                        <label for="confirm-password" class=label_style>
+                            <span class="label-text">{i18n.t("password-confirm")}</span>
                         </label>
-                        <input type="password" node_ref=password class=input_style required />
+                        <input
+                            id="confirm-password"
+                            type="password"
+                            class=input_style
+                            class:input-error=move || confirm_password_error().is_some()
+                            on:input=move |ev| set_confirm_password(event_target_value(&ev))
+                            prop:value=confirm_password
+                            required
+                        />
+                        {move || field_hint(cx, confirm_password_error())}
                     </div>
+                    {move || form_error().map(|e| view! { cx,
+                        <div class="alert alert-error mb-4">{e}</div>
+                    })}
                     <div>
                         <button type="submit" class="btn btn-primary py-2 px-4 rounded-md w-full">
                             {move || if signup_pending() {
-                                "Loading..."
+                                i18n.t("loading")
                             } else {
-                                "Signup"
+                                i18n.t("signup")
                             }}
                         </button>
                     </div>
                 </form>
                 <div class="mt-4 text-center">
-                    <span>"Already have an account? "</span>
+                    <span>{i18n.t("signup-have-account")}" "</span>
                     <a href="/login" class="link link-info">
-                        "Login"
+                        {i18n.t("login")}
                     </a>
                 </div>
             </div>
         </div>
 
-        <ErrorList error_title="Signup failed!".to_string()>
+        <ErrorList error_title=i18n.t("signup-failed")>
             {move || {
-                signup_result().map(|r| r.map(|_| {
-                    let goto = use_navigate(cx);
-                    // FIXME: error handling
-                    goto("/login", NavigateOptions::default()).unwrap()
-                }))
+                signup_result().map(|r| match r {
+                    Ok(()) => {
+                        let goto = use_navigate(cx);
+                        if let Err(e) = goto("/login", NavigateOptions::default()) {
+                            error!("going to login after signup failed: {e}");
+                        }
+                        Ok(())
+                    }
+                    // shown next to the fields instead
+                    Err(e) if FieldErrors::from_error(&e).is_some() => Ok(()),
+                    Err(e) => Err(e),
+                })
             }}
         </ErrorList>
     }
@@ -0,0 +1,240 @@
+use crate::api::{get_my_comments, get_my_topics, get_my_votes, PageInput, Stage};
+use crate::component::*;
+use crate::i18n::use_i18n;
+use crate::state::GlobalState;
+use leptos::*;
+
+/// Renders the profile page, which lists what the current user has participated in
+#[component]
+pub fn ProfilePage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let username = move || state.with(|s| s.user().map(|u| u.username.clone()));
+    let role = move || state.with(|s| s.role().map(|r| i18n.t(r.message_id())));
+
+    view! { cx,
+        <div class="p-4 md:p-16 w-full mx-auto">
+            <h2 class="text-3xl font-semibold">{username}</h2>
+            <span class="badge badge-info">{role}</span>
+            <a href="/me/password" class="link link-info ml-4">{i18n.t("profile-change-password")}</a>
+            <div class="grid grid-cols-1 lg:grid-cols-3 gap-8 mt-8">
+                <MyTopics />
+                <MyVotes />
+                <MyComments />
+            </div>
+        </div>
+    }
+}
+
+fn loading(cx: Scope) -> View {
+    view! { cx, <p>{use_i18n(cx).t("loading")} <span class="loading loading-spinner"></span></p> }
+        .into_view(cx)
+}
+
+#[component]
+fn MyTopics(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let (page, set_page) = create_signal(cx, 1);
+    let topics = create_local_resource(
+        cx,
+        move || (state().token().map(|t| t.to_string()), page()),
+        |(token, page)| async move {
+            let token = token?;
+            let input = PageInput {
+                page,
+                ..Default::default()
+            };
+            Some(get_my_topics(token, input).await)
+        },
+    );
+    let page_count = Signal::derive(cx, move || {
+        topics
+            .read(cx)
+            .flatten()
+            .and_then(|t| t.ok())
+            .map(|t| t.page_count())
+            .unwrap_or_default()
+    });
+    let topics_view = move || {
+        topics.read(cx).flatten().map(|topics| {
+            topics.map(|topics| {
+                if topics.items.is_empty() {
+                    return view! { cx, <p class="p-8 text-center">{i18n.t("profile-no-topics")}</p> }.into_view(cx);
+                }
+                topics
+                    .items
+                    .into_iter()
+                    .map(|topic| {
+                        view! { cx,
+                            <li>
+                                <a href=format!("/topic/{}", topic.id) class="link link-info">
+                                    {topic.description}
+                                </a>
+                                <span class="badge badge-ghost ml-2">
+                                    {topic
+                                        .stage
+                                        .parse::<Stage>()
+                                        .map(|stage| i18n.t(stage.message_id()))
+                                        .unwrap_or(topic.stage)}
+                                </span>
+                            </li>
+                        }
+                    })
+                    .collect_view(cx)
+            })
+        })
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <h3 class="text-2xl pb-4">{i18n.t("profile-my-topics")}</h3>
+            <Transition fallback=move || loading(cx)>
+                <ErrorList error_title=i18n.t("profile-my-topics")>
+                    <ul class="list-disc pl-4">{topics_view}</ul>
+                </ErrorList>
+            </Transition>
+            <Pagination page=page page_count=page_count on_change=set_page />
+        </div>
+    }
+}
+
+#[component]
+fn MyVotes(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let (page, set_page) = create_signal(cx, 1);
+    let votes = create_local_resource(
+        cx,
+        move || (state().token().map(|t| t.to_string()), page()),
+        |(token, page)| async move {
+            let token = token?;
+            let input = PageInput {
+                page,
+                ..Default::default()
+            };
+            Some(get_my_votes(token, input).await)
+        },
+    );
+    let page_count = Signal::derive(cx, move || {
+        votes
+            .read(cx)
+            .flatten()
+            .and_then(|v| v.ok())
+            .map(|v| v.page_count())
+            .unwrap_or_default()
+    });
+    let votes_view = move || {
+        votes.read(cx).flatten().map(|votes| {
+            votes.map(|votes| {
+                if votes.items.is_empty() {
+                    return view! { cx, <p class="p-8 text-center">{i18n.t("profile-no-votes")}</p> }.into_view(cx);
+                }
+                votes
+                    .items
+                    .into_iter()
+                    .map(|vote| {
+                        // the choice is hidden for topics with anonymous ballots
+                        let choice = vote
+                            .option_id
+                            .and_then(|id| vote.topic.options.into_iter().find(|o| o.id == id))
+                            .map(|o| o.label)
+                            .unwrap_or_else(|| i18n.t("profile-vote-hidden"));
+                        view! { cx,
+                            <li>
+                                <a href=format!("/topic/{}", vote.topic.id) class="link link-info">
+                                    {vote.topic.description}
+                                </a>
+                                <span class="ml-2">{i18n.t_args("profile-voted", [("choice", choice.into())])}</span>
+                            </li>
+                        }
+                    })
+                    .collect_view(cx)
+            })
+        })
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <h3 class="text-2xl pb-4">{i18n.t("profile-my-votes")}</h3>
+            <Transition fallback=move || loading(cx)>
+                <ErrorList error_title=i18n.t("profile-my-votes")>
+                    <ul class="list-disc pl-4">{votes_view}</ul>
+                </ErrorList>
+            </Transition>
+            <Pagination page=page page_count=page_count on_change=set_page />
+        </div>
+    }
+}
+
+#[component]
+fn MyComments(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let (page, set_page) = create_signal(cx, 1);
+    let comments = create_local_resource(
+        cx,
+        move || (state().token().map(|t| t.to_string()), page()),
+        |(token, page)| async move {
+            let token = token?;
+            let input = PageInput {
+                page,
+                ..Default::default()
+            };
+            Some(get_my_comments(token, input).await)
+        },
+    );
+    let page_count = Signal::derive(cx, move || {
+        comments
+            .read(cx)
+            .flatten()
+            .and_then(|c| c.ok())
+            .map(|c| c.page_count())
+            .unwrap_or_default()
+    });
+    let comments_view = move || {
+        comments.read(cx).flatten().map(|comments| {
+            comments.map(|comments| {
+                if comments.items.is_empty() {
+                    return view! { cx, <p class="p-8 text-center">{i18n.t("profile-no-comments")}</p> }
+                        .into_view(cx);
+                }
+                comments
+                    .items
+                    .into_iter()
+                    .map(|comment| {
+                        // older backends don't say which topic a comment belongs to
+                        let content = if comment.topic_id.is_empty() {
+                            view! { cx, <span>{comment.content}</span> }.into_view(cx)
+                        } else {
+                            view! { cx,
+                                <a href=format!("/topic/{}", comment.topic_id) class="link link-info">
+                                    {comment.content}
+                                </a>
+                            }
+                            .into_view(cx)
+                        };
+                        view! { cx,
+                            <li>
+                                {content}
+                                <span class="text-sm ml-2">{i18n.format_datetime(&comment.created_at)}</span>
+                            </li>
+                        }
+                    })
+                    .collect_view(cx)
+            })
+        })
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <h3 class="text-2xl pb-4">{i18n.t("profile-my-comments")}</h3>
+            <Transition fallback=move || loading(cx)>
+                <ErrorList error_title=i18n.t("profile-my-comments")>
+                    <ul class="list-disc pl-4">{comments_view}</ul>
+                </ErrorList>
+            </Transition>
+            <Pagination page=page page_count=page_count on_change=set_page />
+        </div>
+    }
+}
@@ -1,22 +1,131 @@
 use crate::api::{
-    get_comments, get_my_vote, get_one_topic, get_vote_result, CreateOptionInput, CreateTopic,
-    CreateTopicInput, CreateVote, CreateVoteInput, GetCommentsInput,
+    get_comments, get_drafts, get_my_vote, get_one_topic, get_templates, get_topics,
+    get_vote_result, Attachment, CreateOptionInput, CreateTemplate, CreateTemplateInput,
+    CreateTopic, CreateTopicInput, CreateVote, CreateVoteInput, DeleteTemplate,
+    DeleteTemplateInput, GetCommentsInput, GetTopicsInput, PageInput, PublishTopic,
+    PublishTopicInput, Role, Stage, Topic, TopicQuery, TopicTemplate, UpdateTopic,
+    UpdateTopicInput,
 };
 use crate::component::*;
+use crate::i18n::use_i18n;
 use crate::state::GlobalState;
+use crate::uri::decode_component;
 use chrono::prelude::*;
 use leptos::ev::SubmitEvent;
 use leptos::*;
 use leptos_router::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+
+/// Lists all topics a page at a time, with the filters and page number in the URL
+///
+/// Under `/tag/:name` only the topics with that tag are listed.
+#[component]
+pub fn TopicsPage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let params = use_query_map(cx);
+    let route_params = use_params_map(cx);
+    let tag = create_memo(cx, move |_| {
+        route_params.with(|p| p.get("name").map(|name| decode_component(name)))
+    });
+    let query = create_memo(cx, move |_| TopicQuery {
+        tag: tag(),
+        ..params.with(topic_query)
+    });
+    let page = create_memo(cx, move |_| {
+        params.with(|p| {
+            p.get("page")
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(1usize)
+                .max(1)
+        })
+    });
+    let topics = create_resource(
+        cx,
+        move || GetTopicsInput {
+            query: query(),
+            page: PageInput {
+                page: page(),
+                ..Default::default()
+            },
+        },
+        get_topics,
+    );
+    let page_count = Signal::derive(cx, move || {
+        topics
+            .read(cx)
+            .and_then(|t| t.ok())
+            .map(|t| t.page_count())
+            .unwrap_or_default()
+    });
+    let location = use_location(cx);
+    let goto_page = move |page: usize| {
+        let href = topic_query_href(&location.pathname.get(), &query());
+        let sep = if href.contains('?') { '&' } else { '?' };
+        let goto = use_navigate(cx);
+        if let Err(e) = goto(
+            &format!("{href}{sep}page={page}"),
+            NavigateOptions::default(),
+        ) {
+            error!("going to page {page} failed: {e}");
+        }
+    };
+    let topics_view = move || {
+        topics.read(cx).map(|topics| {
+            topics.map(|topics| {
+                if topics.items.is_empty() {
+                    return view! { cx, <p class="p-8 text-center">{i18n.t("topics-empty")}</p> }
+                        .into_view(cx);
+                }
+                topics
+                    .items
+                    .into_iter()
+                    .map(|topic| {
+                        let (topic, _) = create_signal(cx, topic);
+                        view! { cx, <TopicCard topic=topic show_action=true /> }
+                    })
+                    .collect_view(cx)
+            })
+        })
+    };
+
+    view! { cx,
+        <div class="p-4">
+            <h2 class="text-3xl font-semibold text-center">
+                {move || match tag() {
+                    Some(tag) => i18n.t_args("topics-tagged", [("tag", tag.into())]),
+                    None => i18n.t("nav-topics"),
+                }}
+            </h2>
+            <TopicFilters query=query />
+            <Transition fallback=move || view! { cx, <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p> }>
+                <ErrorList error_title=i18n.t("nav-topics")>
+                    <div class="flex flex-col items-center w-full mx-auto">
+                        {topics_view}
+                    </div>
+                </ErrorList>
+            </Transition>
+            <Pagination page=page page_count=page_count on_change=goto_page />
+        </div>
+    }
+}
 
 #[component]
 pub fn TopicPage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
     let state = expect_context::<RwSignal<GlobalState>>(cx);
     let is_login = move || state().token().is_some();
+    let is_organizer = move || state.with(|s| s.has_role(Role::Organizer));
     let params = use_params_map(cx);
     let id = params.with(|params| params.get("id").unwrap().to_string());
     let (id, _) = create_signal(cx, id);
-    let topic = create_local_resource(cx, id, move |id| async move { get_one_topic(id).await });
+    let token = move || state.with(|s| s.token().map(|t| t.to_string()));
+    // editors may look at their scheduled topics before they are published
+    let topic = create_local_resource(
+        cx,
+        move || (id(), token()),
+        move |(id, token)| async move { get_one_topic(id, token).await },
+    );
     let create_vote = create_server_action::<CreateVote>(cx);
     let create_vote_pending = create_vote.pending();
     let create_vote_result = create_vote.value();
@@ -33,8 +142,8 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
         },
         move |(token, topic)| async move {
             let (Some(token), Some(topic)) = (token, topic) else {
-                    return None;
-                };
+                return None;
+            };
 
             Some(
                 get_my_vote(
@@ -45,9 +154,13 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
             )
         },
     );
-    let vote_result = create_local_resource(cx, id, move |id| async move {
-        get_vote_result(crate::api::GetVoteResultInput { topic_id: id }).await
-    });
+    let vote_result = create_local_resource(
+        cx,
+        move || (id(), token()),
+        move |(id, token)| async move {
+            get_vote_result(token, crate::api::GetVoteResultInput { topic_id: id }).await
+        },
+    );
     let comments = create_resource(cx, id, move |id| async move {
         get_comments(GetCommentsInput { topic_id: id }).await
     });
@@ -57,7 +170,7 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
                 if comments.is_empty() {
                     view! { cx,
                         <p class="p-8 w-full text-center">
-                            "No comments"
+                            {i18n.t("topic-no-comments")}
                         </p>
                     }
                     .into_view(cx)
@@ -87,9 +200,27 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
         r
     });
 
+    // follows the clock, so voting opens and closes without a reload
+    let now = use_now(cx);
+    let stage = create_memo(cx, move |_| {
+        topic
+            .read(cx)
+            .and_then(|t| t.ok())
+            .and_then(|t| t.stage_at(now()))
+    });
+    let is_voting = move || stage() == Some(Stage::Voting);
+    create_effect(cx, move |prev: Option<Option<Stage>>| {
+        let current = stage();
+        if matches!(prev, Some(Some(prev)) if Some(prev) != current) {
+            vote_result.refetch();
+            my_vote.refetch();
+        }
+        current
+    });
+
     view! { cx,
-        <Transition fallback=move || view! { cx, <p>"Loading..." <span class="loading loading-spinner"></span></p> }>
-            <ErrorList error_title="Topic Page".to_string()>
+        <Transition fallback=move || view! { cx, <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p> }>
+            <ErrorList error_title=i18n.t("topic-page")>
                 {move || topic.read(cx).map(move |topic| {
                     topic.map(|topic| {
                         let (topic, _) = create_signal(cx, topic);
@@ -103,7 +234,7 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
                         let option_cards = topic().options.iter().map(|opt| {
                             let (opt, _) = create_signal(cx, opt.clone());
                             let vote = move |_| {
-                                if create_vote_pending() {
+                                if create_vote_pending() || !is_voting() {
                                     return;
                                 }
                                 let topic_id = topic().id;
@@ -116,24 +247,33 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
                                     input,
                                 });
                             };
-                            let extra_class = my_vote.as_ref().and_then(|v| (opt().id == v.option_id).then(|| "bg-primary")).unwrap_or_default();
+                            let extra_class = my_vote.as_ref().and_then(|v| (opt().id == v.option_id).then_some("bg-primary")).unwrap_or_default();
                             if let Some(Ok(vote_result)) = vote_result.read(cx) {
                                 let total = vote_result.values().sum::<usize>();
-                                let cnt = *vote_result.get(&opt().id).unwrap();
+                                let cnt = vote_result.get(&opt().id).copied().unwrap_or(0);
                                 let ratio = if total == 0 {
                                     0.0
                                 } else {
                                     cnt as f64 / total as f64
-                                } * 100.0;
+                                };
+                                let votes = i18n.t_args(
+                                    "topic-votes",
+                                    [("count", cnt.into()), ("formatted", i18n.format_number(cnt).into())],
+                                );
                                 view! { cx,
                                     <div class="card card-compact w-96 m-4">
+                                        {opt().image.map(|image| view! { cx,
+                                            <figure class="pt-4">
+                                                <Thumbnail image=image alt=opt().label />
+                                            </figure>
+                                        })}
                                         <div class="card-body">
                                             <div class="card-title">
                                                 {opt().label}
                                             </div>
                                             <p>{opt().description}</p>
                                             <progress class="progress progress-primary" value=cnt max=total></progress>
-                                            <span>{format!("{ratio:.2}%")}</span>
+                                            <span>{i18n.format_percent(ratio)}" · "{votes}</span>
                                         </div>
                                     </div>
                                 }.into_view(cx)
@@ -142,17 +282,19 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
                                     <OptionCard
                                         option=opt
                                         extra_class=extra_class.to_string()
-                                        action=Some(move || view! { cx,
+                                        // voting is only offered to logged in users
+                                        action=is_login().then_some(move || view! { cx,
                                             <button
                                                 class="btn"
                                                 on:click=vote
-                                                class:btn-disabled=move ||create_vote_pending() || !is_login()
-                                                class:btn-info=move || !create_vote_pending() && is_login()
+                                                class:btn-disabled=move || create_vote_pending() || !is_voting()
+                                                class:btn-info=move || !create_vote_pending() && is_voting()
                                             >
-                                                {move || if create_vote_pending() {
-                                                    "Loading..."
-                                                } else {
-                                                    "+1"
+                                                {move || match (create_vote_pending(), stage()) {
+                                                    (true, _) => i18n.t("loading"),
+                                                    (false, Some(Stage::Upcoming)) => i18n.t("topic-vote-not-open"),
+                                                    (false, Some(Stage::Ended)) => i18n.t("topic-vote-closed"),
+                                                    (false, _) => "+1".to_string(),
                                                 }}
                                             </button>
                                         })
@@ -165,17 +307,28 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
                             <div class="p-4 md:p-16 w-full mx-auto grid grid-cols-1 lg:grid-cols-2">
                                 <div class="flex flex-col item-center">
                                     {topic_card}
+                                    <AttachmentList attachments=topic().attachments />
+                                    {move || is_organizer().then(|| view! { cx,
+                                        <a href=format!("/topic/create?from={}", id()) class="btn btn-outline btn-sm self-end mb-4">
+                                            {i18n.t("topic-duplicate")}
+                                        </a>
+                                    })}
                                     {move || (!is_login()).then(|| {
                                         view! { cx,
                                             <h2 class="text-center">
-                                                "Login to vote."
+                                                <a href=login_path(&format!("/topic/{}", id())) class="link link-info">
+                                                    {i18n.t("topic-login-to-vote")}
+                                                </a>
                                             </h2>
                                         }
                                     })}
+                                    <ErrorList error_title=i18n.t("topic-vote-failed")>
+                                        {move || create_vote_result().map(|r| r.map(|_| ()))}
+                                    </ErrorList>
                                     {option_cards}
                                 </div>
                                 <div class="flex flex-col">
-                                    <h2 class="text-3xl pb-4">"Comments"</h2>
+                                    <h2 class="text-3xl pb-4">{i18n.t("topic-comments")}</h2>
                                     {comments_view}
                                     {move || view!{ cx, <CreateCommentCard
                                         id=id()
@@ -193,56 +346,320 @@ pub fn TopicPage(cx: Scope) -> impl IntoView {
     }
 }
 
+/// What has been typed into [`CreateTopicPage`], with times as `datetime-local` values
+///
+/// Autosaved to local storage, so a refresh does not lose it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TopicForm {
+    description: String,
+    starts_at: String,
+    ends_at: String,
+    publish_at: String,
+    options: Vec<CreateOptionInput>,
+    tags: Vec<String>,
+    /// Usernames separated by commas
+    editors: String,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+}
+
+impl TopicForm {
+    fn from_topic(topic: &Topic, tz: FixedOffset) -> Self {
+        Self {
+            description: topic.description.clone(),
+            starts_at: utc_to_local(&topic.starts_at, tz),
+            ends_at: utc_to_local(&topic.ends_at, tz),
+            publish_at: topic
+                .publish_at
+                .as_deref()
+                .map(|t| utc_to_local(t, tz))
+                .unwrap_or_default(),
+            options: topic
+                .options
+                .iter()
+                .map(|opt| CreateOptionInput {
+                    label: opt.label.clone(),
+                    description: opt.description.clone(),
+                    image: opt.image.clone(),
+                })
+                .collect(),
+            tags: topic.tags.clone(),
+            editors: topic.editors.join(", "),
+            attachments: topic.attachments.clone(),
+        }
+    }
+
+    /// A copy of `topic` to create anew, moving its start moves its end along
+    fn duplicate(topic: &Topic, tz: FixedOffset) -> Self {
+        Self {
+            publish_at: String::new(),
+            ..Self::from_topic(topic, tz)
+        }
+    }
+
+    /// A new topic from `template`, with the times it was saved with until a new start is
+    /// picked, see [`shift_end`]
+    fn from_template(template: &TopicTemplate, tz: FixedOffset) -> Self {
+        Self {
+            description: template.description.clone(),
+            starts_at: utc_to_local(&template.starts_at, tz),
+            ends_at: utc_to_local(&template.ends_at, tz),
+            options: template.options.clone(),
+            tags: template.tags.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn into_template(self, name: String, tz: FixedOffset) -> CreateTemplateInput {
+        CreateTemplateInput {
+            name,
+            description: self.description,
+            options: self.options,
+            tags: self.tags,
+            starts_at: local_to_utc(&self.starts_at, tz).unwrap_or_default(),
+            ends_at: local_to_utc(&self.ends_at, tz).unwrap_or_default(),
+        }
+    }
+
+    fn into_input(self, draft: bool, tz: FixedOffset) -> CreateTopicInput {
+        CreateTopicInput {
+            description: self.description,
+            // a draft may be saved before its times are known
+            starts_at: local_to_utc(&self.starts_at, tz).unwrap_or_default(),
+            ends_at: local_to_utc(&self.ends_at, tz).unwrap_or_default(),
+            options: self.options,
+            tags: self.tags,
+            draft,
+            publish_at: local_to_utc(&self.publish_at, tz),
+            editors: self
+                .editors
+                .split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .map(str::to_string)
+                .collect(),
+            attachments: self.attachments,
+        }
+    }
+}
+
+/// A `datetime-local` value in the site time zone `tz` to UTC, `None` if empty or invalid
+fn local_to_utc(value: &str, tz: FixedOffset) -> Option<String> {
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").ok()?;
+    let utc = tz.from_local_datetime(&naive).single()?.with_timezone(&Utc);
+
+    Some(utc.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// The inverse of [`local_to_utc`]
+fn utc_to_local(value: &str, tz: FixedOffset) -> String {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&tz).format("%Y-%m-%dT%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// `ends_at` moved by as much as the start moved from `old_starts_at` to `new_starts_at`,
+/// so that picking a new start keeps the length of the voting period
+///
+/// All are `datetime-local` values, `None` if any of them is empty or invalid.
+fn shift_end(old_starts_at: &str, new_starts_at: &str, ends_at: &str) -> Option<String> {
+    let parse = |value| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").ok();
+    let shift = parse(new_starts_at)? - parse(old_starts_at)?;
+
+    Some(
+        (parse(ends_at)? + shift)
+            .format("%Y-%m-%dT%H:%M")
+            .to_string(),
+    )
+}
+
+/// Labels of options pasted one per line, blank lines skipped
+fn option_labels(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Where the form of a new topic, or of the draft `draft_id`, is autosaved
+fn form_storage_key(draft_id: Option<&str>) -> String {
+    match draft_id {
+        Some(id) => format!("vote.topic-form.{id}"),
+        None => "vote.topic-form".to_string(),
+    }
+}
+
+/// Only in the browser
+fn local_storage() -> Option<web_sys::Storage> {
+    window().local_storage().ok().flatten()
+}
+
+/// Creates a topic, or edits the draft given as `?draft=<id>`
+///
+/// A new topic can start as a copy of `?from=<topic id>` or `?template=<template id>`.
 #[component]
 pub fn CreateTopicPage(cx: Scope) -> impl IntoView {
-    use leptos::html::Input;
+    let i18n = use_i18n(cx);
+    let tz = i18n.time_zone();
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let query = use_query_map(cx);
+    let draft_id = store_value(cx, query.with_untracked(|q| q.get("draft").cloned()));
+    let storage_key = store_value(
+        cx,
+        draft_id.with_value(|id| form_storage_key(id.as_deref())),
+    );
 
-    let description: NodeRef<Input> = create_node_ref(cx);
-    let starts_at: NodeRef<Input> = create_node_ref(cx);
-    let ends_at: NodeRef<Input> = create_node_ref(cx);
+    let (description, set_description) = create_signal(cx, String::new());
+    let (starts_at, set_starts_at) = create_signal(cx, String::new());
+    let (ends_at, set_ends_at) = create_signal(cx, String::new());
+    let (publish_at, set_publish_at) = create_signal(cx, String::new());
+    let (editors, set_editors) = create_signal(cx, String::new());
+    let tags = create_rw_signal(cx, Vec::<String>::new());
+    let attachments = create_rw_signal(cx, Vec::<Attachment>::new());
 
-    // let init_options = vec![(0, create_signal(cx, CreateOptionInput::default()))];
-    let init_options = vec![];
-    let (options, set_options) = create_signal(cx, init_options);
-    let mut next_id = 1;
-    let add_option = move |_| {
+    // keyed by an id that stays with an option when it is moved
+    let (options, set_options) = create_signal(cx, vec![]);
+    let next_id = store_value(cx, 0);
+    let insert_options = move |at: usize, new: Vec<CreateOptionInput>| {
         set_options.update(move |options| {
-            options.push((next_id, create_signal(cx, CreateOptionInput::default())));
+            let at = at.min(options.len());
+            let new = new.into_iter().map(|option| {
+                let id = next_id.get_value();
+                next_id.set_value(id + 1);
+                (id, create_signal(cx, option))
+            });
+            options.splice(at..at, new.collect::<Vec<_>>());
+        });
+    };
+    let push_option = move |option: CreateOptionInput| insert_options(usize::MAX, vec![option]);
+    let add_option = move |_| push_option(CreateOptionInput::default());
+    let position = move |id: usize| options.with(|options| options.iter().position(|o| o.0 == id));
+    let remove_option =
+        move |id: usize| set_options.update(|options| options.retain(|o| o.0 != id));
+    // `to` is the index after `id` has been taken out
+    let move_option = move |id: usize, to: usize| {
+        set_options.update(|options| {
+            let Some(from) = options.iter().position(|o| o.0 == id) else {
+                return;
+            };
+            let option = options.remove(from);
+            options.insert(to.min(options.len()), option);
         });
-        next_id += 1;
     };
+    let move_up = move |id: usize| {
+        if let Some(i) = position(id).filter(|i| *i > 0) {
+            move_option(id, i - 1);
+        }
+    };
+    let move_down = move |id: usize| {
+        if let Some(i) = position(id) {
+            move_option(id, i + 1);
+        }
+    };
+    let (dragging, set_dragging) = create_signal(cx, None::<usize>);
 
-    let create_topic = create_server_action::<CreateTopic>(cx);
-    let create_topic_result = create_topic.value();
-    let on_submit = move |ev: SubmitEvent| {
-        ev.prevent_default();
+    let form = move || TopicForm {
+        description: description(),
+        starts_at: starts_at(),
+        ends_at: ends_at(),
+        publish_at: publish_at(),
+        options: options().into_iter().map(|(_, (opt, _))| opt()).collect(),
+        tags: tags(),
+        editors: editors(),
+        attachments: attachments(),
+    };
+    let fill = move |form: TopicForm| {
+        set_description(form.description);
+        set_starts_at(form.starts_at);
+        set_ends_at(form.ends_at);
+        set_publish_at(form.publish_at);
+        set_editors(form.editors);
+        tags.set(form.tags);
+        attachments.set(form.attachments);
+        set_options(vec![]);
+        for option in form.options {
+            push_option(option);
+        }
+    };
 
-        let description = description().expect("<input> to exist").value();
-        let starts_at = starts_at().expect("<input> to exist").value();
-        let ends_at = ends_at().expect("<input> to exist").value();
-        let options = options().into_iter().map(|(_, (opt, _))| opt()).collect();
-
-        let now = Local::now();
-        let starts_at = DateTime::<Utc>::from_utc(
-            NaiveDateTime::parse_from_str(&starts_at, "%Y-%m-%dT%H:%M")
-                .expect("should be a datetime string"),
-            Utc,
-        ) - *now.offset();
-
-        let ends_at = DateTime::<Utc>::from_utc(
-            NaiveDateTime::parse_from_str(&ends_at, "%Y-%m-%dT%H:%M")
-                .expect("should be a datetime string"),
-            Utc,
-        ) - *now.offset();
-
-        let input = CreateTopicInput {
-            description,
-            starts_at: format!("{starts_at:?}"),
-            ends_at: format!("{ends_at:?}"),
-            options,
+    // restore what was typed before a refresh, then save every change
+    let restored = store_value(cx, false);
+    create_effect(cx, move |prev: Option<()>| {
+        let current = form();
+        let Some(storage) = local_storage() else {
+            return;
         };
+        let key = storage_key.get_value();
+        if prev.is_none() {
+            let saved = storage
+                .get_item(&key)
+                .ok()
+                .flatten()
+                .and_then(|json| serde_json::from_str(&json).ok());
+            if let Some(saved) = saved {
+                restored.set_value(true);
+                fill(saved);
+            }
+        } else if let Ok(json) = serde_json::to_string(&current) {
+            _ = storage.set_item(&key, &json);
+        }
+    });
 
-        create_topic.dispatch(CreateTopic { input });
+    let from_id = store_value(cx, query.with_untracked(|q| q.get("from").cloned()));
+    let template_id = store_value(cx, query.with_untracked(|q| q.get("template").cloned()));
+    let source = create_local_resource(
+        cx,
+        move || state.with(|s| s.token().map(|t| t.to_string())),
+        move |token| async move {
+            if let Some(id) = draft_id.get_value() {
+                let drafts = get_drafts(token?).await.ok()?;
+                let draft = drafts.into_iter().find(|topic| topic.id == id)?;
+                // unsaved changes in local storage win over the saved draft
+                return (!restored.get_value()).then(|| TopicForm::from_topic(&draft, tz));
+            }
+            if let Some(id) = from_id.get_value() {
+                let topic = get_one_topic(id, token).await.ok()?;
+                return Some(TopicForm::duplicate(&topic, tz));
+            }
+            let id = template_id.get_value()?;
+            let templates = get_templates(token?).await.ok()?;
+            templates
+                .iter()
+                .find(|template| template.id == id)
+                .map(|template| TopicForm::from_template(template, tz))
+        },
+    );
+    create_effect(cx, move |_| {
+        if let Some(Some(form)) = source.read(cx) {
+            fill(form);
+        }
+    });
+
+    let create_topic = create_server_action::<CreateTopic>(cx);
+    let update_topic = create_server_action::<UpdateTopic>(cx);
+    let result = move || create_topic.value()().or_else(|| update_topic.value()());
+    let saved_as_draft = store_value(cx, false);
+    let submit = move |draft: bool| {
+        let Some(token) = state().token().map(|t| t.to_string()) else {
+            return;
+        };
+        let input = form().into_input(draft, tz);
+        saved_as_draft.set_value(draft);
+        match draft_id.get_value() {
+            Some(topic_id) => update_topic.dispatch(UpdateTopic {
+                token,
+                input: UpdateTopicInput {
+                    topic_id,
+                    topic: input,
+                },
+            }),
+            None => create_topic.dispatch(CreateTopic { token, input }),
+        }
+    };
+    let on_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        submit(false);
     };
 
     let input_style = "input input-bordered input-info w-full max-w-md";
@@ -250,62 +667,241 @@ pub fn CreateTopicPage(cx: Scope) -> impl IntoView {
     view! { cx,
         <div class="max-w-md mx-auto mt-8">
             <div class="rounded-lg shadow-md p-8">
-                <h2 class="text-2xl font-semibold mb-6">"Create Topic"</h2>
+                <h2 class="text-2xl font-semibold mb-6">
+                    {move || if draft_id.with_value(Option::is_some) { i18n.t("topic-form-edit-draft") } else { i18n.t("topic-form-create") }}
+                </h2>
+                {move || draft_id.with_value(Option::is_none).then(|| view! { cx,
+                    <Templates form=form fill=fill />
+                })}
                 <form on:submit=on_submit>
                     <div class="mb-4">
                         <label for="description" class="">
-                            <span class="label-text">"Description"</span>
+                            <span class="label-text">{i18n.t("topic-form-description")}</span>
                         </label>
-                        <input type="text" id="description" name="description" node_ref=description class=input_style required />
+                        <input
+                            type="text"
+                            id="description"
+                            name="description"
+                            class=input_style
+                            prop:value=description
+                            on:input=move |ev| set_description(event_target_value(&ev))
+                            required
+                        />
                     </div>
+                    <p class="mb-2 text-sm opacity-70">
+                        {i18n.t_args("topic-form-time-zone", [("offset", tz.to_string().into())])}
+                    </p>
                     <div class="mb-4">
                         <label for="starts_at" class="">
-                            <span class="label-text">"Starts At"</span>
+                            <span class="label-text">{i18n.t("topic-form-starts-at")}</span>
                         </label>
-                        <input type="datetime-local" id="starts_at" name="starts_at" node_ref=starts_at class=input_style required />
+                        <input
+                            type="datetime-local"
+                            id="starts_at"
+                            name="starts_at"
+                            class=input_style
+                            prop:value=starts_at
+                            on:input=move |ev| {
+                                let new = event_target_value(&ev);
+                                let end = ends_at.get_untracked();
+                                if let Some(end) = shift_end(&starts_at.get_untracked(), &new, &end) {
+                                    set_ends_at(end);
+                                }
+                                set_starts_at(new);
+                            }
+                            required
+                        />
                     </div>
                     <div class="mb-4">
                         <label for="ends_at" class="">
-                            <span class="label-text">"Ends At"</span>
+                            <span class="label-text">{i18n.t("topic-form-ends-at")}</span>
                         </label>
-                        <input type="datetime-local" id="ends_at" name="ends_at" node_ref=ends_at class=input_style required />
+                        <input
+                            type="datetime-local"
+                            id="ends_at"
+                            name="ends_at"
+                            class=input_style
+                            prop:value=ends_at
+                            on:input=move |ev| set_ends_at(event_target_value(&ev))
+                            required
+                        />
+                    </div>
+                    <div class="mb-4">
+                        <label for="publish_at" class="">
+                            <span class="label-text">{i18n.t("topic-form-publish-at")}</span>
+                        </label>
+                        <input
+                            type="datetime-local"
+                            id="publish_at"
+                            name="publish_at"
+                            class=input_style
+                            prop:value=publish_at
+                            on:input=move |ev| set_publish_at(event_target_value(&ev))
+                        />
+                    </div>
+                    <div class="mb-4">
+                        <label for="tags" class="">
+                            <span class="label-text">{i18n.t("nav-tags")}</span>
+                        </label>
+                        <TagPicker tags=tags />
+                    </div>
+                    <div class="mb-4">
+                        <label for="attachments" class="">
+                            <span class="label-text">{i18n.t("topic-form-documents")}</span>
+                        </label>
+                        <AttachmentPicker attachments=attachments />
+                    </div>
+                    <div class="mb-4">
+                        <label for="editors" class="">
+                            <span class="label-text">{i18n.t("topic-form-editors")}</span>
+                        </label>
+                        <input
+                            type="text"
+                            id="editors"
+                            name="editors"
+                            class=input_style
+                            placeholder=i18n.t("topic-form-editors-placeholder")
+                            prop:value=editors
+                            on:input=move |ev| set_editors(event_target_value(&ev))
+                        />
                     </div>
                     <div>
-                        <h3 class="text-lg font-semibold mb-2">"Options"</h3>
+                        <h3 class="text-lg font-semibold mb-2">{i18n.t("topic-form-options")}</h3>
                         <div id="options-container">
                             <For
                                 each=options
                                 key=|option| option.0
-                                view=move |cx, (_, (option, set_option))| {
+                                view=move |cx, (id, (option, set_option))| {
+                                    let image = Signal::derive(cx, move || option.with(|o| o.image.clone()));
                                     // seems to be a bug, this value is actually used
                                     #[allow(unused)]
                                     let option = option();
+                                    // several lines pasted into a label become an option each
+                                    let on_paste = move |ev: ev::Event| {
+                                        // web-sys only exposes ClipboardEvent behind its unstable APIs
+                                        let text = js_sys::Reflect::get(&ev, &"clipboardData".into())
+                                            .ok()
+                                            .and_then(|data| data.dyn_into::<web_sys::DataTransfer>().ok())
+                                            .and_then(|data| data.get_data("text/plain").ok())
+                                            .unwrap_or_default();
+                                        let mut labels = option_labels(&text);
+                                        if labels.len() < 2 {
+                                            return;
+                                        }
+                                        ev.prevent_default();
+                                        let first = labels.remove(0);
+                                        set_option.update(|opt| opt.label = first);
+                                        let new = labels
+                                            .into_iter()
+                                            .map(|label| CreateOptionInput { label, ..Default::default() })
+                                            .collect();
+                                        if let Some(i) = position(id) {
+                                            insert_options(i + 1, new);
+                                        }
+                                    };
                                     view! { cx,
-                                        <div class="mb-4">
-                                            <input
-                                                type="text"
-                                                name="option-label[]"
-                                                class=input_style
-                                                placeholder="Option Label"
-                                                prop:value=option.label
-                                                on:input=move |ev| {
-                                                    set_option.update(|opt| {
-                                                        opt.label = event_target_value(&ev);
-                                                    })
+                                        <div
+                                            class=move || if dragging() == Some(id) {
+                                                "mb-4 flex gap-2 items-start opacity-50"
+                                            } else {
+                                                "mb-4 flex gap-2 items-start"
+                                            }
+                                            on:dragover=move |ev: ev::DragEvent| ev.prevent_default()
+                                            on:drop=move |ev: ev::DragEvent| {
+                                                ev.prevent_default();
+                                                if let (Some(dragged), Some(to)) = (dragging(), position(id)) {
+                                                    move_option(dragged, to);
+                                                }
+                                                set_dragging(None);
+                                            }
+                                        >
+                                            <button
+                                                type="button"
+                                                class="btn btn-ghost btn-sm cursor-move"
+                                                draggable="true"
+                                                aria-label=i18n.t("topic-form-drag")
+                                                on:dragstart=move |ev: ev::DragEvent| {
+                                                    // some browsers only start a drag with data
+                                                    if let Some(data) = ev.data_transfer() {
+                                                        _ = data.set_data("text/plain", &id.to_string());
+                                                        data.set_effect_allowed("move");
+                                                    }
+                                                    set_dragging(Some(id));
                                                 }
-                                                required
-                                            />
-                                            <textarea
-                                                name="option-description[]"
-                                                class="textarea textarea-info mt-2 w-full max-w-md"
-                                                placeholder="Option Description"
-                                                on:input=move |ev| {
-                                                    set_option.update(|opt| {
-                                                        opt.description = event_target_value(&ev);
-                                                    })
+                                                on:dragend=move |_| set_dragging(None)
+                                                on:keydown=move |ev: ev::KeyboardEvent| match ev.key().as_str() {
+                                                    "ArrowUp" => {
+                                                        ev.prevent_default();
+                                                        move_up(id);
+                                                    }
+                                                    "ArrowDown" => {
+                                                        ev.prevent_default();
+                                                        move_down(id);
+                                                    }
+                                                    _ => {}
                                                 }
-                                                prop:value=option.description
-                                            ></textarea>
+                                            >
+                                                "⠿"
+                                            </button>
+                                            <div class="flex-1">
+                                                <input
+                                                    type="text"
+                                                    name="option-label[]"
+                                                    class=input_style
+                                                    placeholder=i18n.t("topic-form-option-label")
+                                                    prop:value=option.label
+                                                    on:input=move |ev| {
+                                                        set_option.update(|opt| {
+                                                            opt.label = event_target_value(&ev);
+                                                        })
+                                                    }
+                                                    on:paste=on_paste
+                                                    required
+                                                />
+                                                <textarea
+                                                    name="option-description[]"
+                                                    class="textarea textarea-info mt-2 w-full max-w-md"
+                                                    placeholder=i18n.t("topic-form-option-description")
+                                                    on:input=move |ev| {
+                                                        set_option.update(|opt| {
+                                                            opt.description = event_target_value(&ev);
+                                                        })
+                                                    }
+                                                    prop:value=option.description
+                                                ></textarea>
+                                                <ImagePicker
+                                                    image=image
+                                                    on_change=move |image| set_option.update(|opt| opt.image = image)
+                                                />
+                                            </div>
+                                            <div class="flex flex-col">
+                                                <button
+                                                    type="button"
+                                                    class="btn btn-ghost btn-xs"
+                                                    aria-label=i18n.t("topic-form-move-up")
+                                                    class:btn-disabled={move || position(id) == Some(0)}
+                                                    on:click=move |_| move_up(id)
+                                                >
+                                                    "▲"
+                                                </button>
+                                                <button
+                                                    type="button"
+                                                    class="btn btn-ghost btn-xs"
+                                                    aria-label=i18n.t("topic-form-move-down")
+                                                    class:btn-disabled={move || position(id).map(|i| i + 1) == Some(options.with(Vec::len))}
+                                                    on:click=move |_| move_down(id)
+                                                >
+                                                    "▼"
+                                                </button>
+                                                <button
+                                                    type="button"
+                                                    class="btn btn-ghost btn-xs text-error"
+                                                    aria-label=i18n.t("topic-form-remove-option")
+                                                    on:click=move |_| remove_option(id)
+                                                >
+                                                    "✕"
+                                                </button>
+                                            </div>
                                         </div>
                                     }
                                 }
@@ -317,27 +913,342 @@ pub fn CreateTopicPage(cx: Scope) -> impl IntoView {
                             class="btn btn-info py-2 px-4"
                             on:click=add_option
                         >
-                            "Add Option"
+                            {i18n.t("topic-form-add-option")}
                         </button>
                     </div>
-                    <div class="mt-6">
+                    <div class="mt-6 flex gap-2">
+                        <button
+                            type="button"
+                            class="btn btn-outline py-2 px-4 flex-1"
+                            on:click=move |_| submit(true)
+                        >
+                            {i18n.t("topic-form-save-draft")}
+                        </button>
                         <button
                             type="submit"
-                            class="btn btn-success py-2 px-4 w-full"
+                            class="btn btn-success py-2 px-4 flex-1"
                         >
-                            "Create"
+                            {move || if publish_at().is_empty() { i18n.t("topic-form-submit") } else { i18n.t("topic-form-schedule") }}
                         </button>
                     </div>
                 </form>
             </div>
         </div>
 
-        <ErrorList error_title="Create Topic Failed".to_string()>
-            {move || create_topic_result().map(|resp| resp.map(|_| {
+        <ErrorList error_title=i18n.t("topic-form-failed")>
+            {move || result().map(|resp| resp.map(|_| {
+                if let Some(storage) = local_storage() {
+                    _ = storage.remove_item(&storage_key.get_value());
+                }
                 let goto = use_navigate(cx);
-                // FIXME: error handling
-                goto("/", NavigateOptions::default())
+                let next = if saved_as_draft.get_value() { "/me/drafts" } else { "/" };
+                // a failed navigation is listed like a failed save
+                goto(next, NavigateOptions::default())
             }))}
         </ErrorList>
     }
 }
+
+/// Drafts and scheduled topics of the current user, to be edited or published
+#[component]
+pub fn DraftsPage(cx: Scope) -> impl IntoView {
+    let i18n = use_i18n(cx);
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let publish_topic = create_server_action::<PublishTopic>(cx);
+    let publish_pending = publish_topic.pending();
+    let drafts = create_local_resource(
+        cx,
+        move || {
+            (
+                state.with(|s| s.token().map(|t| t.to_string())),
+                publish_topic.version()(),
+            )
+        },
+        |(token, _)| async move {
+            match token {
+                Some(token) => Some(get_drafts(token).await),
+                None => None,
+            }
+        },
+    );
+
+    let publish = move |topic_id: String| {
+        if publish_pending() {
+            return;
+        }
+        let Some(token) = state().token().map(|t| t.to_string()) else {
+            return;
+        };
+        publish_topic.dispatch(PublishTopic {
+            token,
+            input: PublishTopicInput {
+                topic_id,
+                publish_at: None,
+            },
+        });
+    };
+
+    let drafts_view = move || {
+        drafts.read(cx).flatten().map(|drafts| {
+            drafts.map(|drafts| {
+                if drafts.is_empty() {
+                    return view! { cx, <p class="p-8 w-full text-center">{i18n.t("drafts-empty")}</p> }
+                        .into_view(cx);
+                }
+
+                drafts
+                    .into_iter()
+                    .map(|topic| {
+                        let status = match (&topic.draft, &topic.publish_at) {
+                            (true, _) => i18n.t("drafts-draft"),
+                            (false, Some(publish_at)) => i18n.t_args(
+                                "drafts-scheduled",
+                                [("time", i18n.format_datetime(publish_at).into())],
+                            ),
+                            (false, None) => i18n.t("drafts-publishing"),
+                        };
+                        let editors = (!topic.editors.is_empty())
+                            .then(|| {
+                                i18n.t_args("drafts-editors", [("editors", topic.editors.join(", ").into())])
+                            });
+                        let id = topic.id.clone();
+                        view! { cx,
+                            <div class="card bg-base-200 mb-4 shadow-xl">
+                                <div class="card-body">
+                                    <div class="text-2xl font-semibold">{topic.description}</div>
+                                    <span class="badge badge-ghost">{status}</span>
+                                    <p class="text-sm">
+                                        {i18n.t("drafts-created-by")}{topic.creator} <br />
+                                        {editors}
+                                    </p>
+                                    <div class="card-actions justify-end">
+                                        <a href=format!("/topic/create?draft={}", topic.id) class="btn">
+                                            {i18n.t("drafts-edit")}
+                                        </a>
+                                        <button
+                                            class="btn btn-success"
+                                            class:btn-disabled=publish_pending
+                                            on:click=move |_| publish(id.clone())
+                                        >
+                                            {i18n.t("drafts-publish")}
+                                        </button>
+                                    </div>
+                                </div>
+                            </div>
+                        }
+                    })
+                    .collect_view(cx)
+            })
+        })
+    };
+
+    view! { cx,
+        <div class="p-4 max-w-3xl mx-auto">
+            <h2 class="text-3xl pb-4">{i18n.t("nav-drafts")}</h2>
+            <Transition fallback=move || view! { cx, <p>{i18n.t("loading")} <span class="loading loading-spinner"></span></p> }>
+                <ErrorList error_title=i18n.t("nav-drafts")>
+                    {drafts_view}
+                </ErrorList>
+            </Transition>
+            <ErrorList error_title=i18n.t("drafts-publish-failed")>
+                {move || publish_topic.value()().map(|r| r.map(|_| ()))}
+            </ErrorList>
+        </div>
+    }
+}
+
+/// Fills [`CreateTopicPage`] from a saved template, or saves the form as one
+#[component]
+fn Templates<F, G>(cx: Scope, form: F, fill: G) -> impl IntoView
+where
+    F: Fn() -> TopicForm + Copy + 'static,
+    G: Fn(TopicForm) + Copy + 'static,
+{
+    let i18n = use_i18n(cx);
+    let tz = i18n.time_zone();
+    let state = expect_context::<RwSignal<GlobalState>>(cx);
+    let token = move || state.with(|s| s.token().map(|t| t.to_string()));
+    let create_template = create_server_action::<CreateTemplate>(cx);
+    let delete_template = create_server_action::<DeleteTemplate>(cx);
+    let templates = create_local_resource(
+        cx,
+        move || {
+            (
+                token(),
+                create_template.version()(),
+                delete_template.version()(),
+            )
+        },
+        |(token, _, _)| async move {
+            match token {
+                Some(token) => get_templates(token).await.ok(),
+                None => None,
+            }
+        },
+    );
+    let (selected, set_selected) = create_signal(cx, String::new());
+    let (name, set_name) = create_signal(cx, String::new());
+
+    let use_template = move |_| {
+        let templates = templates.read(cx).flatten().unwrap_or_default();
+        if let Some(template) = templates.iter().find(|t| t.id == selected()) {
+            fill(TopicForm::from_template(template, tz));
+        }
+    };
+    let delete = move |_| {
+        let Some(token) = token() else {
+            return;
+        };
+        let templates = templates.read(cx).flatten().unwrap_or_default();
+        let Some(template) = templates.iter().find(|t| t.id == selected()) else {
+            return;
+        };
+        let question = i18n.t_args(
+            "template-delete-confirm",
+            [("name", template.name.as_str().into())],
+        );
+        if !window().confirm_with_message(&question).unwrap_or(false) {
+            return;
+        }
+        delete_template.dispatch(DeleteTemplate {
+            token,
+            input: DeleteTemplateInput {
+                template_id: selected(),
+            },
+        });
+        set_selected(String::new());
+    };
+    let save = move |_| {
+        let Some(token) = token() else {
+            return;
+        };
+        create_template.dispatch(CreateTemplate {
+            token,
+            input: form().into_template(name(), tz),
+        });
+        set_name(String::new());
+    };
+    let template_options = move || {
+        templates
+            .read(cx)
+            .flatten()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|template| {
+                let id = template.id.clone();
+                view! { cx,
+                    <option value=template.id selected=move || selected() == id>
+                        {template.name}
+                    </option>
+                }
+            })
+            .collect_view(cx)
+    };
+    let nothing_selected = move || selected().is_empty();
+
+    view! { cx,
+        <div class="mb-6">
+            <label for="template" class="">
+                <span class="label-text">{i18n.t("template")}</span>
+            </label>
+            <div class="join w-full">
+                <select
+                    id="template"
+                    class="select select-bordered select-info join-item flex-1"
+                    on:change=move |ev| set_selected(event_target_value(&ev))
+                >
+                    <option value="" selected=nothing_selected>{i18n.t("template-pick")}</option>
+                    {template_options}
+                </select>
+                <button
+                    type="button"
+                    class="btn btn-info join-item"
+                    class:btn-disabled=nothing_selected
+                    on:click=use_template
+                >
+                    {i18n.t("template-use")}
+                </button>
+                <button
+                    type="button"
+                    class="btn btn-error join-item"
+                    class:btn-disabled=nothing_selected
+                    on:click=delete
+                >
+                    {i18n.t("template-delete")}
+                </button>
+            </div>
+            <div class="join w-full mt-2">
+                <input
+                    type="text"
+                    class="input input-bordered join-item flex-1"
+                    placeholder=i18n.t("template-name")
+                    prop:value=name
+                    on:input=move |ev| set_name(event_target_value(&ev))
+                />
+                <button
+                    type="button"
+                    class="btn join-item"
+                    class:btn-disabled={move || name().trim().is_empty()}
+                    on:click=save
+                >
+                    {i18n.t("template-save")}
+                </button>
+            </div>
+            <ErrorList error_title=i18n.t("template-failed")>
+                {move || create_template.value()().map(|r| r.map(|_| ()))}
+                {move || delete_template.value()().map(|r| r.map(|_| ()))}
+            </ErrorList>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picking_a_new_start_moves_the_end_along() {
+        assert_eq!(
+            shift_end("2024-02-19T08:00", "2024-09-16T09:30", "2024-02-23T17:00").as_deref(),
+            Some("2024-09-20T18:30")
+        );
+        // backwards as well, e.g. when the start was typed wrong
+        assert_eq!(
+            shift_end("2024-09-16T09:00", "2024-09-09T09:00", "2024-09-20T09:00").as_deref(),
+            Some("2024-09-13T09:00")
+        );
+    }
+
+    #[test]
+    fn nothing_to_shift_without_all_times() {
+        assert_eq!(shift_end("", "2024-09-16T09:00", "2024-09-20T09:00"), None);
+        assert_eq!(shift_end("2024-09-16T09:00", "", "2024-09-20T09:00"), None);
+        assert_eq!(shift_end("2024-09-16T09:00", "2024-09-17T09:00", ""), None);
+    }
+
+    #[test]
+    fn pasted_lines_become_option_labels() {
+        assert_eq!(option_labels("Yes\nNo\n"), ["Yes", "No"]);
+        assert_eq!(
+            option_labels("  Compilers \r\n\r\n\tDatabases\n   \n"),
+            ["Compilers", "Databases"]
+        );
+        assert_eq!(option_labels("Just one"), ["Just one"]);
+        assert!(option_labels("\n \n").is_empty());
+    }
+
+    #[test]
+    fn form_times_are_in_the_site_time_zone() {
+        let taipei = FixedOffset::east_opt(8 * 3600).unwrap();
+        assert_eq!(
+            local_to_utc("2024-09-16T09:30", taipei).as_deref(),
+            Some("2024-09-16T01:30:00Z")
+        );
+        assert_eq!(
+            utc_to_local("2024-09-16T01:30:00Z", taipei),
+            "2024-09-16T09:30"
+        );
+        assert_eq!(local_to_utc("", taipei), None);
+        assert_eq!(utc_to_local("soon", taipei), "");
+    }
+}
@@ -0,0 +1,153 @@
+//! The JS, WASM and CSS bundle under `/pkg`.
+//!
+//! `scripts/build-assets.sh` runs after a release build: it copies the bundle to names
+//! with a content hash, writes the hash to `pkg/hash` and puts a brotli and a gzip copy
+//! next to every file. If the hash file exists, pages link the hashed names, which are
+//! cached for a year. Everything else, HTML included, has to be revalidated.
+
+use actix_files::NamedFile;
+use actix_web::{
+    http::header::{self, AcceptEncoding, ContentEncoding, Encoding, Header},
+    middleware::DefaultHeaders,
+    web, HttpRequest, HttpResponse,
+};
+use leptos::LeptosOptions;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// Written by `scripts/build-assets.sh`, next to the bundle
+const HASH_FILE: &str = "hash";
+
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+const REVALIDATE: &str = "no-cache";
+
+struct Assets {
+    dir: PathBuf,
+    pkg_dir: String,
+    output_name: String,
+    hashed: bool,
+}
+
+static ASSETS: OnceLock<Assets> = OnceLock::new();
+
+fn assets() -> &'static Assets {
+    ASSETS.get().expect("assets should be initialized")
+}
+
+/// Switch `options` to the hashed bundle if there is one
+///
+/// Must be called before the app is rendered.
+pub fn init(options: &mut LeptosOptions) -> io::Result<()> {
+    let dir = Path::new(&options.site_root).join(&options.site_pkg_dir);
+    let hash = match std::fs::read_to_string(dir.join(HASH_FILE)) {
+        Ok(hash) => Some(hash.trim().to_string()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    if let Some(hash) = &hash {
+        if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a hash: {hash:?}", dir.join(HASH_FILE).display()),
+            ));
+        }
+        options.output_name = format!("{}.{hash}", options.output_name);
+        tracing::info!("serving assets hashed {hash}");
+    }
+
+    let assets = Assets {
+        dir,
+        pkg_dir: options.site_pkg_dir.clone(),
+        output_name: options.output_name.clone(),
+        hashed: hash.is_some(),
+    };
+    ASSETS
+        .set(assets)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "assets are already initialized"))
+}
+
+/// Where pages load the stylesheet from
+pub fn stylesheet() -> String {
+    let assets = assets();
+    format!("/{}/{}.css", assets.pkg_dir, assets.output_name)
+}
+
+/// `Cache-Control` for responses without one, so HTML is always revalidated
+pub fn cache_headers() -> DefaultHeaders {
+    DefaultHeaders::new().add((header::CACHE_CONTROL, REVALIDATE))
+}
+
+/// Register `/pkg/...`
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.route(
+        &format!("/{}/{{file:.*}}", assets().pkg_dir),
+        web::get().to(serve),
+    );
+}
+
+async fn serve(req: HttpRequest, file: web::Path<String>) -> actix_web::Result<HttpResponse> {
+    let assets = assets();
+    let file = file.into_inner();
+    if file
+        .split('/')
+        .any(|s| s.is_empty() || s.starts_with('.') || s.contains('\\'))
+    {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+    let path = assets.dir.join(&file);
+
+    // only offer the precompressed copies that were built
+    let mut supported = vec![Encoding::identity()];
+    for (encoding, ext) in [(Encoding::brotli(), "br"), (Encoding::gzip(), "gz")] {
+        if with_extension(&path, ext).is_file() {
+            supported.push(encoding);
+        }
+    }
+    let encoding = match AcceptEncoding::parse(&req) {
+        Ok(accept) => accept.negotiate(supported.iter()),
+        Err(_) => None,
+    };
+
+    let named = match encoding {
+        Some(Encoding::Known(ContentEncoding::Brotli)) => {
+            NamedFile::open_async(with_extension(&path, "br"))
+                .await?
+                .set_content_encoding(ContentEncoding::Brotli)
+        }
+        Some(Encoding::Known(ContentEncoding::Gzip)) => {
+            NamedFile::open_async(with_extension(&path, "gz"))
+                .await?
+                .set_content_encoding(ContentEncoding::Gzip)
+        }
+        _ => NamedFile::open_async(&path).await?,
+    };
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let mut res = named
+        .set_content_type(actix_files::file_extension_to_mime(ext))
+        .disable_content_disposition()
+        .into_response(&req);
+
+    // a hashed name never changes its content
+    let immutable = assets.hashed && file.starts_with(&format!("{}.", assets.output_name));
+    let headers = res.headers_mut();
+    headers.insert(
+        header::CACHE_CONTROL,
+        header::HeaderValue::from_static(if immutable { IMMUTABLE } else { REVALIDATE }),
+    );
+    headers.insert(
+        header::VARY,
+        header::HeaderValue::from_static("accept-encoding"),
+    );
+
+    Ok(res)
+}
+
+fn with_extension(path: &Path, ext: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(ext);
+    path.into()
+}
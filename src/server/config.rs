@@ -0,0 +1,857 @@
+//! Server configuration.
+//!
+//! Settings are read from a TOML file (`--config`, `VOTE_CONFIG`, or `vote.toml` if
+//! present), then overridden by `VOTE_*` environment variables, then by command line
+//! flags. See `vote.example.toml` for every setting and its default.
+
+use crate::server::rate_limit::{effective_limits, Limit};
+use anyhow::Context;
+use chrono::FixedOffset;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+    time::Duration,
+};
+
+/// Used if neither `--config` nor `VOTE_CONFIG` is given
+const DEFAULT_CONFIG_FILE: &str = "vote.toml";
+
+#[derive(Debug, Parser)]
+#[command(version, about = "NTNU CSIE voting frontend server")]
+pub struct Args {
+    /// Configuration file [default: vote.toml if it exists]
+    #[arg(short, long, env = "VOTE_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Address to listen on [default: Leptos `site-addr`]
+    #[arg(long)]
+    pub bind: Option<SocketAddr>,
+    /// Base URL of the backend API
+    #[arg(long)]
+    pub backend_url: Option<String>,
+    /// Log filter, e.g. `info` or `warn,vote=debug`
+    #[arg(long)]
+    pub log_level: Option<String>,
+    /// Print the effective configuration and exit
+    #[arg(long)]
+    pub print_config: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub backend: BackendConfig,
+    pub cookie: CookieConfig,
+    pub features: Features,
+    pub health: HealthConfig,
+    pub log: LogConfig,
+    pub metrics: MetricsConfig,
+    pub security: SecurityConfig,
+    pub mail: MailConfig,
+    pub oidc: OidcConfig,
+    pub uploads: UploadsConfig,
+    /// Per server fn name, overriding the defaults in `server::rate_limit`
+    pub rate_limits: BTreeMap<String, Limit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Falls back to the Leptos `site-addr`
+    pub bind: Option<SocketAddr>,
+    /// Address users reach this site at, used for links in mails and SSO redirects
+    pub public_url: String,
+    /// Seconds a client has to send the request head
+    pub request_timeout_secs: u64,
+    /// Seconds to keep serving after SIGTERM while `/readyz` reports not ready, so
+    /// load balancers stop sending new requests
+    pub shutdown_delay_secs: u64,
+    /// Seconds in-flight requests get to finish once the server stops accepting
+    /// connections, the rest are aborted
+    pub shutdown_grace_secs: u64,
+    /// Serve HTTPS instead of HTTP
+    pub tls: Option<TlsConfig>,
+    /// Reverse proxies whose `X-Forwarded-For` is believed when rate limiting by client IP
+    pub trusted_proxies: Vec<IpAddr>,
+    /// UTC offset every page shows times in, e.g. `+08:00`, whatever the time zone of the
+    /// server or the browser. Daylight saving time is not followed.
+    pub time_zone: String,
+}
+
+impl ServerConfig {
+    pub fn time_zone(&self) -> FixedOffset {
+        // validated at startup
+        self.time_zone
+            .parse()
+            .unwrap_or(FixedOffset::east_opt(0).unwrap())
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: None,
+            public_url: "http://localhost:3000".to_string(),
+            request_timeout_secs: 5,
+            shutdown_delay_secs: 5,
+            shutdown_grace_secs: 30,
+            tls: None,
+            trusted_proxies: Vec::new(),
+            time_zone: "+08:00".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM certificate chain
+    pub cert: PathBuf,
+    /// PEM private key
+    pub key: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackendConfig {
+    pub url: String,
+    /// Seconds a whole backend call may take
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:8000".to_string(),
+            timeout_secs: 10,
+            connect_timeout_secs: 3,
+        }
+    }
+}
+
+impl BackendConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SameSite {
+    Strict,
+    #[default]
+    Lax,
+    None,
+}
+
+impl FromStr for SameSite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(Self::Strict),
+            "lax" => Ok(Self::Lax),
+            "none" => Ok(Self::None),
+            _ => Err("expected strict, lax or none".to_string()),
+        }
+    }
+}
+
+impl From<SameSite> for actix_web::cookie::SameSite {
+    fn from(same_site: SameSite) -> Self {
+        match same_site {
+            SameSite::Strict => Self::Strict,
+            SameSite::Lax => Self::Lax,
+            SameSite::None => Self::None,
+        }
+    }
+}
+
+/// Attributes of cookies set by the server
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CookieConfig {
+    /// Only send cookies over HTTPS
+    pub secure: bool,
+    pub same_site: SameSite,
+}
+
+/// Parts of the site that can be switched off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Features {
+    pub signup: bool,
+    /// "Login with school account", also needs `[oidc]`
+    pub sso: bool,
+    pub rate_limit: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            signup: true,
+            sso: true,
+            rate_limit: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One JSON object per line
+    #[default]
+    Json,
+    /// Human readable, for development
+    Text,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            _ => Err("expected json or text".to_string()),
+        }
+    }
+}
+
+/// See `server::health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthConfig {
+    /// Seconds `/readyz` waits for the backend
+    pub probe_timeout_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            probe_timeout_secs: 2,
+        }
+    }
+}
+
+impl HealthConfig {
+    pub fn probe_timeout(&self) -> Duration {
+        Duration::from_secs(self.probe_timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// `tracing` filter, e.g. `info` or `warn,vote=debug`
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailSenderKind {
+    Smtp,
+    #[default]
+    File,
+    /// Only for development, mails contain login tokens
+    Log,
+}
+
+impl FromStr for MailSenderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smtp" => Ok(Self::Smtp),
+            "file" => Ok(Self::File),
+            "log" => Ok(Self::Log),
+            _ => Err("expected smtp, file or log".to_string()),
+        }
+    }
+}
+
+/// See `server::metrics`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Bearer token required to scrape `/metrics`, only localhost may scrape if unset
+    pub token: Option<String>,
+}
+
+/// See `server::security`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityConfig {
+    /// `{nonce}` is replaced by a fresh nonce for the inline scripts of every response
+    pub content_security_policy: String,
+    /// Only sent if `server.public_url` is HTTPS, 0 to disable
+    pub hsts_max_age_secs: u64,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            content_security_policy: [
+                "default-src 'self'",
+                "script-src 'self' 'nonce-{nonce}' 'wasm-unsafe-eval'",
+                "style-src 'self' 'unsafe-inline'",
+                "img-src 'self' data:",
+                "connect-src 'self'",
+                "object-src 'none'",
+                "base-uri 'self'",
+                "form-action 'self'",
+                "frame-ancestors 'none'",
+            ]
+            .join("; "),
+            hsts_max_age_secs: 60 * 60 * 24 * 365,
+        }
+    }
+}
+
+/// See `server::mail`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    pub sender: MailSenderKind,
+    pub from: String,
+    /// Where the `file` sender writes mails
+    pub dir: PathBuf,
+    pub smtp_host: Option<String>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            sender: MailSenderKind::File,
+            from: "NTNU CSIE Voting <noreply@localhost>".to_string(),
+            dir: PathBuf::from("target/mail"),
+            smtp_host: None,
+            smtp_username: None,
+            smtp_password: None,
+        }
+    }
+}
+
+/// OpenID Connect provider, see `server::oidc`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OidcConfig {
+    pub issuer: Option<String>,
+    pub client_id: Option<String>,
+    /// Not needed by public clients
+    pub client_secret: Option<String>,
+}
+
+/// See `server::upload`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UploadsConfig {
+    /// Where uploaded files and thumbnails are stored
+    pub dir: PathBuf,
+    /// Largest picture accepted, in bytes
+    pub max_image_bytes: usize,
+    /// Largest PDF accepted, in bytes
+    pub max_document_bytes: usize,
+    /// How long an upload is kept without a topic or template referring to it
+    pub orphan_ttl_secs: u64,
+}
+
+impl Default for UploadsConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("target/uploads"),
+            max_image_bytes: 5 * 1024 * 1024,
+            max_document_bytes: 20 * 1024 * 1024,
+            orphan_ttl_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the file, then applies environment variables and flags
+    pub fn load(args: &Args) -> anyhow::Result<Self> {
+        Self::load_with_env(args, |name| std::env::var(name).ok())
+    }
+
+    fn load_with_env(args: &Args, env: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(env)?;
+        config.apply_args(args);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read config file {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    /// `VOTE_*` environment variables, named after the settings they override
+    fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) -> anyhow::Result<()> {
+        fn parse<T>(name: &str, value: String) -> anyhow::Result<T>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("invalid {name}={value:?}: {e}"))
+        }
+
+        if let Some(v) = env("VOTE_BIND") {
+            self.server.bind = Some(parse("VOTE_BIND", v)?);
+        }
+        if let Some(v) = env("VOTE_PUBLIC_URL") {
+            self.server.public_url = v;
+        }
+        if let Some(v) = env("VOTE_REQUEST_TIMEOUT_SECS") {
+            self.server.request_timeout_secs = parse("VOTE_REQUEST_TIMEOUT_SECS", v)?;
+        }
+        if let Some(v) = env("VOTE_SHUTDOWN_DELAY_SECS") {
+            self.server.shutdown_delay_secs = parse("VOTE_SHUTDOWN_DELAY_SECS", v)?;
+        }
+        if let Some(v) = env("VOTE_SHUTDOWN_GRACE_SECS") {
+            self.server.shutdown_grace_secs = parse("VOTE_SHUTDOWN_GRACE_SECS", v)?;
+        }
+        match (env("VOTE_TLS_CERT"), env("VOTE_TLS_KEY")) {
+            (Some(cert), Some(key)) => {
+                self.server.tls = Some(TlsConfig {
+                    cert: cert.into(),
+                    key: key.into(),
+                })
+            }
+            (None, None) => {}
+            _ => anyhow::bail!("VOTE_TLS_CERT and VOTE_TLS_KEY must be set together"),
+        }
+        if let Some(v) = env("VOTE_TRUSTED_PROXIES") {
+            // e.g. `127.0.0.1,10.0.0.2`
+            self.server.trusted_proxies = v
+                .split(',')
+                .filter(|ip| !ip.trim().is_empty())
+                .map(|ip| parse("VOTE_TRUSTED_PROXIES", ip.trim().to_string()))
+                .collect::<anyhow::Result<_>>()?;
+        }
+        if let Some(v) = env("VOTE_TIME_ZONE") {
+            self.server.time_zone = v;
+        }
+        if let Some(v) = env("VOTE_BACKEND_URL") {
+            self.backend.url = v;
+        }
+        if let Some(v) = env("VOTE_BACKEND_TIMEOUT_SECS") {
+            self.backend.timeout_secs = parse("VOTE_BACKEND_TIMEOUT_SECS", v)?;
+        }
+        if let Some(v) = env("VOTE_BACKEND_CONNECT_TIMEOUT_SECS") {
+            self.backend.connect_timeout_secs = parse("VOTE_BACKEND_CONNECT_TIMEOUT_SECS", v)?;
+        }
+        if let Some(v) = env("VOTE_COOKIE_SECURE") {
+            self.cookie.secure = parse("VOTE_COOKIE_SECURE", v)?;
+        }
+        if let Some(v) = env("VOTE_COOKIE_SAME_SITE") {
+            self.cookie.same_site = parse("VOTE_COOKIE_SAME_SITE", v)?;
+        }
+        if let Some(v) = env("VOTE_FEATURE_SIGNUP") {
+            self.features.signup = parse("VOTE_FEATURE_SIGNUP", v)?;
+        }
+        if let Some(v) = env("VOTE_FEATURE_SSO") {
+            self.features.sso = parse("VOTE_FEATURE_SSO", v)?;
+        }
+        if let Some(v) = env("VOTE_FEATURE_RATE_LIMIT") {
+            self.features.rate_limit = parse("VOTE_FEATURE_RATE_LIMIT", v)?;
+        }
+        if let Some(v) = env("VOTE_HEALTH_PROBE_TIMEOUT_SECS") {
+            self.health.probe_timeout_secs = parse("VOTE_HEALTH_PROBE_TIMEOUT_SECS", v)?;
+        }
+        if let Some(v) = env("VOTE_LOG_LEVEL") {
+            self.log.level = v;
+        }
+        if let Some(v) = env("VOTE_LOG_FORMAT") {
+            self.log.format = parse("VOTE_LOG_FORMAT", v)?;
+        }
+        if let Some(v) = env("VOTE_METRICS_TOKEN") {
+            self.metrics.token = Some(v);
+        }
+        if let Some(v) = env("VOTE_CONTENT_SECURITY_POLICY") {
+            self.security.content_security_policy = v;
+        }
+        if let Some(v) = env("VOTE_HSTS_MAX_AGE_SECS") {
+            self.security.hsts_max_age_secs = parse("VOTE_HSTS_MAX_AGE_SECS", v)?;
+        }
+        if let Some(v) = env("VOTE_MAIL_SENDER") {
+            self.mail.sender = parse("VOTE_MAIL_SENDER", v)?;
+        }
+        if let Some(v) = env("VOTE_MAIL_FROM") {
+            self.mail.from = v;
+        }
+        if let Some(v) = env("VOTE_MAIL_DIR") {
+            self.mail.dir = v.into();
+        }
+        if let Some(v) = env("VOTE_SMTP_HOST") {
+            self.mail.smtp_host = Some(v);
+        }
+        if let Some(v) = env("VOTE_SMTP_USERNAME") {
+            self.mail.smtp_username = Some(v);
+        }
+        if let Some(v) = env("VOTE_SMTP_PASSWORD") {
+            self.mail.smtp_password = Some(v);
+        }
+        if let Some(v) = env("VOTE_OIDC_ISSUER") {
+            self.oidc.issuer = Some(v);
+        }
+        if let Some(v) = env("VOTE_OIDC_CLIENT_ID") {
+            self.oidc.client_id = Some(v);
+        }
+        if let Some(v) = env("VOTE_OIDC_CLIENT_SECRET") {
+            self.oidc.client_secret = Some(v);
+        }
+        if let Some(v) = env("VOTE_UPLOADS_DIR") {
+            self.uploads.dir = v.into();
+        }
+        if let Some(v) = env("VOTE_UPLOADS_MAX_IMAGE_BYTES") {
+            self.uploads.max_image_bytes = parse("VOTE_UPLOADS_MAX_IMAGE_BYTES", v)?;
+        }
+        if let Some(v) = env("VOTE_UPLOADS_MAX_DOCUMENT_BYTES") {
+            self.uploads.max_document_bytes = parse("VOTE_UPLOADS_MAX_DOCUMENT_BYTES", v)?;
+        }
+        if let Some(v) = env("VOTE_UPLOADS_ORPHAN_TTL_SECS") {
+            self.uploads.orphan_ttl_secs = parse("VOTE_UPLOADS_ORPHAN_TTL_SECS", v)?;
+        }
+        if let Some(v) = env("VOTE_RATE_LIMITS") {
+            // e.g. `create_access_token=5/60,signup=3/3600`
+            for entry in v.split(',').filter(|e| !e.trim().is_empty()) {
+                let (name, limit) = entry
+                    .split_once('=')
+                    .with_context(|| format!("VOTE_RATE_LIMITS entry {entry:?} has no '='"))?;
+                let limit = parse("VOTE_RATE_LIMITS", limit.to_string())?;
+                self.rate_limits.insert(name.trim().to_string(), limit);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(bind) = args.bind {
+            self.server.bind = Some(bind);
+        }
+        if let Some(url) = &args.backend_url {
+            self.backend.url = url.clone();
+        }
+        if let Some(level) = &args.log_level {
+            self.log.level = level.clone();
+        }
+    }
+
+    /// Catches mistakes that would otherwise only show up on first use
+    fn validate(&self) -> anyhow::Result<()> {
+        for (name, url) in [
+            ("server.public_url", &self.server.public_url),
+            ("backend.url", &self.backend.url),
+        ] {
+            anyhow::ensure!(
+                url.starts_with("http://") || url.starts_with("https://"),
+                "{name} should be an http(s) URL, got {url:?}"
+            );
+            anyhow::ensure!(
+                !url.ends_with('/'),
+                "{name} should not end with '/', got {url:?}"
+            );
+        }
+        anyhow::ensure!(
+            self.backend.timeout_secs > 0 && self.backend.connect_timeout_secs > 0,
+            "backend timeouts should not be zero"
+        );
+        anyhow::ensure!(
+            self.server.time_zone.parse::<FixedOffset>().is_ok(),
+            "server.time_zone should be a UTC offset like +08:00, got {:?}",
+            self.server.time_zone
+        );
+        anyhow::ensure!(
+            self.server.shutdown_grace_secs > 0,
+            "server.shutdown_grace_secs should not be zero"
+        );
+        anyhow::ensure!(
+            self.health.probe_timeout_secs > 0,
+            "health.probe_timeout_secs should not be zero"
+        );
+        if let Some(tls) = &self.server.tls {
+            for path in [&tls.cert, &tls.key] {
+                anyhow::ensure!(path.is_file(), "TLS file {} does not exist", path.display());
+            }
+        }
+
+        anyhow::ensure!(
+            self.cookie.same_site != SameSite::None || self.cookie.secure,
+            "cookie.same_site = \"none\" requires cookie.secure = true"
+        );
+        anyhow::ensure!(
+            !(self.sso_configured() && self.cookie.same_site == SameSite::Strict),
+            "cookie.same_site = \"strict\" breaks login with school account, use \"lax\""
+        );
+
+        let sample = self
+            .security
+            .content_security_policy
+            .replace(crate::server::security::NONCE_PLACEHOLDER, "0");
+        actix_web::http::header::HeaderValue::from_str(&sample)
+            .context("security.content_security_policy should be a valid header value")?;
+
+        tracing_subscriber::EnvFilter::try_new(&self.log.level)
+            .with_context(|| format!("invalid log.level {:?}", self.log.level))?;
+
+        self.mail
+            .from
+            .parse::<lettre::message::Mailbox>()
+            .with_context(|| format!("invalid mail.from {:?}", self.mail.from))?;
+        anyhow::ensure!(
+            self.mail.sender != MailSenderKind::Smtp || self.mail.smtp_host.is_some(),
+            "mail.sender = \"smtp\" requires mail.smtp_host"
+        );
+        anyhow::ensure!(
+            cfg!(debug_assertions) || self.mail.sender != MailSenderKind::Log,
+            "mail.sender = \"log\" would write login tokens into the log, use \"file\" or \"smtp\""
+        );
+
+        anyhow::ensure!(
+            self.oidc.issuer.is_some() == self.oidc.client_id.is_some(),
+            "oidc.issuer and oidc.client_id must be set together"
+        );
+
+        anyhow::ensure!(
+            self.uploads.max_image_bytes > 0 && self.uploads.max_document_bytes > 0,
+            "upload size limits should not be zero"
+        );
+
+        Ok(())
+    }
+
+    /// Whether "Login with school account" should be offered
+    pub fn sso_configured(&self) -> bool {
+        self.features.sso && self.oidc.issuer.is_some() && self.oidc.client_id.is_some()
+    }
+
+    /// The configuration as TOML with secrets masked and default rate limits filled
+    /// in, for `--print-config`
+    pub fn to_redacted_toml(&self) -> String {
+        let mut config = self.clone();
+        config.rate_limits = effective_limits(&self.rate_limits);
+        for secret in [
+            &mut config.mail.smtp_password,
+            &mut config.oidc.client_secret,
+            &mut config.metrics.token,
+        ] {
+            if secret.is_some() {
+                *secret = Some("<redacted>".to_string());
+            }
+        }
+
+        toml::to_string_pretty(&config).expect("config should serialize")
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Makes the configuration available to [`config`], done once at startup
+pub fn init(config: Config) {
+    CONFIG
+        .set(config)
+        .expect("config should only be initialized once");
+}
+
+/// The configuration loaded at startup
+pub fn config() -> &'static Config {
+    CONFIG
+        .get()
+        .expect("config should be initialized at startup")
+}
+
+/// Address users reach this site at, without trailing `/`
+pub fn public_url() -> &'static str {
+    &config().server.public_url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        let vars = vars.iter().copied().collect::<HashMap<_, _>>();
+        move |name| vars.get(name).map(|v| v.to_string())
+    }
+
+    fn args(config: Option<PathBuf>) -> Args {
+        Args {
+            config,
+            bind: None,
+            backend_url: None,
+            log_level: None,
+            print_config: false,
+        }
+    }
+
+    #[test]
+    fn env_overrides_settings() {
+        let mut config = Config::default();
+        config
+            .apply_env(env(&[
+                ("VOTE_BIND", "0.0.0.0:8080"),
+                ("VOTE_BACKEND_TIMEOUT_SECS", "3"),
+                ("VOTE_SHUTDOWN_GRACE_SECS", "45"),
+                ("VOTE_HSTS_MAX_AGE_SECS", "0"),
+                ("VOTE_COOKIE_SAME_SITE", "strict"),
+                ("VOTE_FEATURE_SIGNUP", "false"),
+                ("VOTE_MAIL_SENDER", "smtp"),
+                ("VOTE_TRUSTED_PROXIES", "127.0.0.1, ::1"),
+                ("VOTE_TIME_ZONE", "-05:00"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.server.bind, Some("0.0.0.0:8080".parse().unwrap()));
+        assert_eq!(config.backend.timeout_secs, 3);
+        assert_eq!(config.server.shutdown_grace_secs, 45);
+        assert_eq!(config.security.hsts_max_age_secs, 0);
+        assert_eq!(config.cookie.same_site, SameSite::Strict);
+        assert!(!config.features.signup);
+        assert_eq!(config.mail.sender, MailSenderKind::Smtp);
+        assert_eq!(
+            config.server.trusted_proxies,
+            [
+                "127.0.0.1".parse::<IpAddr>().unwrap(),
+                "::1".parse().unwrap()
+            ]
+        );
+        assert_eq!(
+            config.server.time_zone(),
+            FixedOffset::west_opt(5 * 3600).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_env_names_the_variable() {
+        for (name, value) in [
+            ("VOTE_BIND", "localhost"),
+            ("VOTE_BACKEND_TIMEOUT_SECS", "soon"),
+            ("VOTE_FEATURE_SSO", "yes please"),
+            ("VOTE_MAIL_SENDER", "pigeon"),
+            ("VOTE_TRUSTED_PROXIES", "proxy.local"),
+        ] {
+            let error = Config::default()
+                .apply_env(env(&[(name, value)]))
+                .unwrap_err();
+            assert!(error.to_string().contains(name), "{error}");
+        }
+
+        let error = Config::default()
+            .apply_env(env(&[("VOTE_TLS_CERT", "cert.pem")]))
+            .unwrap_err();
+        assert!(error.to_string().contains("VOTE_TLS_KEY"));
+    }
+
+    #[test]
+    fn env_rate_limits() {
+        let mut config = Config::default();
+        config
+            .apply_env(env(&[(
+                "VOTE_RATE_LIMITS",
+                "create_access_token=10/60, signup = 1/3600,",
+            )]))
+            .unwrap();
+        assert_eq!(
+            config.rate_limits["create_access_token"].to_string(),
+            "10/60"
+        );
+        assert_eq!(config.rate_limits["signup"].to_string(), "1/3600");
+
+        for invalid in ["signup", "signup=1", "signup=1/0"] {
+            assert!(
+                Config::default()
+                    .apply_env(env(&[("VOTE_RATE_LIMITS", invalid)]))
+                    .is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn flags_beat_env_beat_file() {
+        let path = std::env::temp_dir().join(format!("vote-test-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+            [server]
+            public_url = "https://vote.example.edu"
+
+            [backend]
+            url = "http://file:8000"
+            timeout_secs = 7
+
+            [log]
+            level = "debug"
+            "#,
+        )
+        .unwrap();
+
+        let mut args = args(Some(path.clone()));
+        args.backend_url = Some("http://flag:8000".to_string());
+        let config = Config::load_with_env(
+            &args,
+            env(&[
+                ("VOTE_BACKEND_URL", "http://env:8000"),
+                ("VOTE_BACKEND_TIMEOUT_SECS", "9"),
+            ]),
+        );
+        std::fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+
+        // only in the file
+        assert_eq!(config.server.public_url, "https://vote.example.edu");
+        assert_eq!(config.log.level, "debug");
+        // file and env
+        assert_eq!(config.backend.timeout_secs, 9);
+        // file, env and flag
+        assert_eq!(config.backend.url, "http://flag:8000");
+    }
+
+    #[test]
+    fn invalid_settings_fail_to_load() {
+        let config = Config::load_with_env(
+            &args(None),
+            env(&[("VOTE_PUBLIC_URL", "https://vote.example.edu/")]),
+        );
+        assert!(config.is_err());
+
+        let config = Config::load_with_env(&args(None), env(&[("VOTE_TIME_ZONE", "Asia/Taipei")]));
+        assert!(config.unwrap_err().to_string().contains("time_zone"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Cross-site request forgery protection for server fns.
+//!
+//! Browsers tell us where a request comes from in `Origin` (or at least `Referer`).
+//! `POST`s to `/api` coming from another site (scheme or host differ from this request
+//! and from `server.public_url`) are rejected with 403, and mutating
+//! server fns must carry one of the two headers at all.
+
+use crate::server::config::public_url;
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, Method},
+    Error, HttpResponse,
+};
+use std::{
+    collections::HashSet,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+/// Scheme and host (with port) of an absolute URL, e.g. `http://localhost:3000` of
+/// `http://localhost:3000/a`
+fn origin_of(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    Some(&url[..scheme.len() + 3 + host.len()])
+}
+
+/// Whether the request was sent by a page of this site
+fn is_same_site(req: &ServiceRequest, public_url: &str, source: &str) -> bool {
+    let Some(source) = origin_of(source) else {
+        // e.g. `Origin: null` from sandboxed frames
+        return false;
+    };
+    let info = req.connection_info();
+    source == format!("{}://{}", info.scheme(), info.host())
+        || Some(source) == origin_of(public_url)
+}
+
+/// Decide whether a `POST` to a server fn may pass
+fn check(
+    req: &ServiceRequest,
+    public_url: &str,
+    protected: &HashSet<String>,
+) -> Result<(), &'static str> {
+    let headers = req.headers();
+    let source = headers
+        .get(header::ORIGIN)
+        .or_else(|| headers.get(header::REFERER))
+        .and_then(|v| v.to_str().ok());
+
+    match source {
+        Some(source) if is_same_site(req, public_url, source) => Ok(()),
+        Some(_) => Err("cross-site request rejected"),
+        None if protected.contains(req.path()) => Err("missing Origin or Referer header"),
+        None => Ok(()),
+    }
+}
+
+/// Middleware checking `Origin` / `Referer` of server fn calls
+#[derive(Clone)]
+pub struct OriginCheck {
+    /// Full paths (`/api/...`) of server fns that change state
+    protected: Arc<HashSet<String>>,
+}
+
+impl OriginCheck {
+    pub fn new<I, S>(protected: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            protected: Arc::new(protected.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for OriginCheck
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = OriginCheckMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(OriginCheckMiddleware {
+            service: Rc::new(service),
+            protected: self.protected.clone(),
+        }))
+    }
+}
+
+pub struct OriginCheckMiddleware<S> {
+    service: Rc<S>,
+    protected: Arc<HashSet<String>>,
+}
+
+impl<S, B> Service<ServiceRequest> for OriginCheckMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if req.method() == Method::POST && req.path().starts_with("/api/") {
+            if let Err(reason) = check(&req, public_url(), &self.protected) {
+                let resp = HttpResponse::Forbidden().body(reason);
+                return Box::pin(async move { Ok(req.into_response(resp).map_into_right_body()) });
+            }
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    const PUBLIC_URL: &str = "https://vote.example.edu";
+
+    fn check_post(headers: &[(header::HeaderName, &str)]) -> Result<(), &'static str> {
+        let mut req = TestRequest::post()
+            .uri("/api/create_vote123")
+            .insert_header((header::HOST, "localhost:3000"));
+        for (name, value) in headers {
+            req = req.insert_header((name.clone(), *value));
+        }
+        let protected = HashSet::from(["/api/create_vote123".to_string()]);
+
+        check(&req.to_srv_request(), PUBLIC_URL, &protected)
+    }
+
+    #[test]
+    fn accept_this_site() {
+        assert!(check_post(&[(header::ORIGIN, "http://localhost:3000")]).is_ok());
+        assert!(check_post(&[(header::ORIGIN, "https://vote.example.edu")]).is_ok());
+        assert!(check_post(&[(header::REFERER, "http://localhost:3000/topic/1?a=b")]).is_ok());
+    }
+
+    #[test]
+    fn reject_other_sites() {
+        for origin in [
+            "https://evil.com",
+            "http://localhost:3001",
+            "https://localhost:3000",
+            "http://vote.example.edu",
+            "https://vote.example.edu.evil.com",
+            "null",
+        ] {
+            assert!(check_post(&[(header::ORIGIN, origin)]).is_err(), "{origin}");
+        }
+        assert!(check_post(&[(header::REFERER, "https://evil.com/localhost:3000")]).is_err());
+    }
+
+    #[test]
+    fn origin_wins_over_referer() {
+        assert!(check_post(&[
+            (header::ORIGIN, "https://evil.com"),
+            (header::REFERER, "http://localhost:3000/"),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn missing_header_only_passes_for_unprotected_server_fns() {
+        assert!(check_post(&[]).is_err());
+
+        let req = TestRequest::post()
+            .uri("/api/get_topics123")
+            .insert_header((header::HOST, "localhost:3000"))
+            .to_srv_request();
+        assert!(check(&req, PUBLIC_URL, &HashSet::new()).is_ok());
+    }
+}
@@ -0,0 +1,140 @@
+//! Liveness and readiness probes for the orchestrator.
+//!
+//! `/healthz` answers as long as the server runs, `/readyz` also checks that the
+//! backend answers a cheap `get_topics` within `health.probe_timeout_secs`, and fails
+//! once a shutdown has begun. Both are plain routes outside `/api`, so they are never
+//! rate limited.
+
+use crate::api::{get_topics, GetTopicsInput, PageInput};
+use crate::server::config::config;
+use crate::server::shutdown::is_shutting_down;
+use actix_web::{http::StatusCode, web, HttpResponse};
+use serde::Serialize;
+use std::{collections::BTreeMap, time::Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+struct Component {
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Component {
+    fn ok() -> Self {
+        Self {
+            status: Status::Ok,
+            latency_ms: None,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    status: Status,
+    version: &'static str,
+    /// Set at build time through `VOTE_GIT_SHA`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<&'static str>,
+    components: BTreeMap<&'static str, Component>,
+}
+
+impl Report {
+    fn new(components: BTreeMap<&'static str, Component>) -> Self {
+        let status = if components.values().all(|c| c.status == Status::Ok) {
+            Status::Ok
+        } else {
+            Status::Error
+        };
+
+        Self {
+            status,
+            version: env!("CARGO_PKG_VERSION"),
+            commit: option_env!("VOTE_GIT_SHA"),
+            components,
+        }
+    }
+
+    fn into_response(self) -> HttpResponse {
+        let code = match self.status {
+            Status::Ok => StatusCode::OK,
+            Status::Error => StatusCode::SERVICE_UNAVAILABLE,
+        };
+
+        HttpResponse::build(code)
+            .insert_header(("Cache-Control", "no-store"))
+            .json(self)
+    }
+}
+
+/// Register `/healthz` and `/readyz`
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/healthz", web::get().to(healthz))
+        .route("/readyz", web::get().to(readyz));
+}
+
+async fn healthz() -> HttpResponse {
+    Report::new(BTreeMap::from([("server", Component::ok())])).into_response()
+}
+
+async fn readyz() -> HttpResponse {
+    let server = if is_shutting_down() {
+        Component {
+            status: Status::Error,
+            latency_ms: None,
+            error: Some("shutting down".to_string()),
+        }
+    } else {
+        Component::ok()
+    };
+
+    Report::new(BTreeMap::from([
+        ("server", server),
+        ("backend", probe_backend().await),
+    ]))
+    .into_response()
+}
+
+async fn probe_backend() -> Component {
+    let started_at = Instant::now();
+    let input = GetTopicsInput {
+        page: PageInput {
+            page: 1,
+            page_size: 1,
+        },
+        ..Default::default()
+    };
+    let result = tokio::time::timeout(config().health.probe_timeout(), get_topics(input)).await;
+    let latency_ms = Some(started_at.elapsed().as_millis());
+    // the details (e.g. the backend's response) go to the log, not to anyone asking
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => {
+            tracing::warn!("backend probe failed: {e}");
+            Some("unavailable".to_string())
+        }
+        Err(_) => {
+            tracing::warn!("backend probe timed out");
+            Some("timed out".to_string())
+        }
+    };
+
+    Component {
+        status: if error.is_none() {
+            Status::Ok
+        } else {
+            Status::Error
+        },
+        latency_ms,
+        error,
+    }
+}
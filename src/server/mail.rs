@@ -0,0 +1,212 @@
+//! Outgoing mail, e.g. password reset and email verification links.
+//!
+//! Mail goes through a pluggable [`MailSender`], picked by `mail.sender` in the config:
+//! - `smtp`: deliver via STARTTLS to `mail.smtp_host`, logging in with
+//!   `mail.smtp_username` and `mail.smtp_password` if set
+//! - `file` (default): write every mail into `mail.dir` as an `.eml` file
+//! - `log`: print every mail to the log, refused in release builds since mails carry tokens
+
+use crate::server::config::{MailConfig, MailSenderKind};
+use anyhow::Context;
+use async_trait::async_trait;
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use std::{path::PathBuf, sync::OnceLock};
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()>;
+}
+
+/// Builds the RFC 5322 message shared by all senders
+fn build_message(from: &Mailbox, mail: &Mail) -> anyhow::Result<Message> {
+    let to = mail
+        .to
+        .parse::<Mailbox>()
+        .with_context(|| format!("invalid recipient {:?}", mail.to))?;
+    let message = Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(&mail.subject)
+        .body(mail.body.clone())?;
+
+    Ok(message)
+}
+
+pub struct SmtpSender {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpSender {
+    pub fn new(
+        host: &str,
+        credentials: Option<Credentials>,
+        from: Mailbox,
+    ) -> anyhow::Result<Self> {
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .context("invalid SMTP host")?;
+        if let Some(credentials) = credentials {
+            builder = builder.credentials(credentials);
+        }
+
+        Ok(Self {
+            from,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl MailSender for SmtpSender {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()> {
+        let message = build_message(&self.from, &mail)?;
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+}
+
+/// Writes every mail to a directory, handy for local testing
+pub struct FileSender {
+    from: Mailbox,
+    dir: PathBuf,
+}
+
+impl FileSender {
+    pub fn new(dir: impl Into<PathBuf>, from: Mailbox) -> Self {
+        Self {
+            from,
+            dir: dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl MailSender for FileSender {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()> {
+        let message = build_message(&self.from, &mail)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // nothing of the mail in the name, the recipient is made up by whoever asks
+        let name = format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.f"),
+            uuid::Uuid::new_v4()
+        );
+        tokio::fs::write(self.dir.join(name), message.formatted()).await?;
+
+        Ok(())
+    }
+}
+
+/// Prints every mail instead of sending it
+pub struct LogSender;
+
+#[async_trait]
+impl MailSender for LogSender {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()> {
+        tracing::info!(
+            to = mail.to,
+            subject = mail.subject,
+            "[mail]\n{}",
+            mail.body
+        );
+
+        Ok(())
+    }
+}
+
+static MAILER: OnceLock<Box<dyn MailSender>> = OnceLock::new();
+
+/// The sender picked by `mail`
+fn sender(mail: &MailConfig) -> anyhow::Result<Box<dyn MailSender>> {
+    let from = mail
+        .from
+        .parse::<Mailbox>()
+        .with_context(|| format!("invalid mail.from {:?}", mail.from))?;
+    let sender: Box<dyn MailSender> = match mail.sender {
+        MailSenderKind::Smtp => {
+            let host = mail
+                .smtp_host
+                .as_deref()
+                .context("mail.smtp_host is required by the smtp mail sender")?;
+            let credentials = mail.smtp_username.clone().map(|username| {
+                Credentials::new(username, mail.smtp_password.clone().unwrap_or_default())
+            });
+            Box::new(SmtpSender::new(host, credentials, from)?)
+        }
+        MailSenderKind::File => Box::new(FileSender::new(&mail.dir, from)),
+        MailSenderKind::Log => Box::new(LogSender),
+    };
+
+    Ok(sender)
+}
+
+/// Set up the configured mail sender, called once at startup
+pub fn init(mail: &MailConfig) -> anyhow::Result<()> {
+    let sender = sender(mail)?;
+    anyhow::ensure!(
+        MAILER.set(sender).is_ok(),
+        "mail sender should only be initialized once"
+    );
+
+    Ok(())
+}
+
+/// The mail sender set up at startup, see [`init`]
+pub fn mailer() -> &'static dyn MailSender {
+    MAILER
+        .get()
+        .expect("mail sender should be initialized at startup")
+        .as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn file_names_leave_out_the_recipient() {
+        let dir = std::env::temp_dir().join(format!("vote-mail-{}", uuid::Uuid::new_v4()));
+        let sender = FileSender::new(&dir, "Vote <noreply@localhost>".parse().unwrap());
+        let mail = Mail {
+            to: "a/../../b@example.edu".to_string(),
+            subject: "Reset your password".to_string(),
+            body: "hi".to_string(),
+        };
+        // not a valid mailbox, nothing is written
+        assert!(sender.send(mail.clone()).await.is_err());
+
+        let mail = Mail {
+            to: "student@example.edu".to_string(),
+            ..mail
+        };
+        sender.send(mail).await.unwrap();
+        let names = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names.len(), 1);
+        assert!(names[0].ends_with(".eml") && !names[0].contains("example.edu"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn smtp_needs_a_host() {
+        let mail = MailConfig {
+            sender: MailSenderKind::Smtp,
+            ..Default::default()
+        };
+        assert!(sender(&mail).is_err());
+        assert!(sender(&MailConfig::default()).is_ok());
+    }
+}
@@ -0,0 +1,311 @@
+//! Prometheus metrics, served at `/metrics`.
+//!
+//! Requests are counted per route and per server fn, backend calls per status, and
+//! votes per topic. Turnout per topic should not be public during an election, so
+//! scrapes have to send `metrics.token` as a bearer token, or come from this machine
+//! if no token is configured.
+
+use crate::server::config::config;
+use crate::server::rate_limit::{request_ip, server_fn_name};
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web, Error, HttpRequest, HttpResponse,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::{
+    future::{ready, Future, Ready},
+    net::IpAddr,
+    pin::Pin,
+    rc::Rc,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    server_fn_calls: IntCounterVec,
+    server_fn_duration: HistogramVec,
+    backend_calls: IntCounterVec,
+    backend_duration: HistogramVec,
+    ssr_renders_active: IntGauge,
+    votes_cast: IntCounterVec,
+    votes_rejected: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("vote".to_string()), None)?;
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"],
+        )?;
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route",
+            ),
+            &["method", "route"],
+        )?;
+        let server_fn_calls = IntCounterVec::new(
+            Opts::new(
+                "server_fn_calls_total",
+                "Server fn calls by name and status",
+            ),
+            &["server_fn", "status"],
+        )?;
+        let server_fn_duration = HistogramVec::new(
+            HistogramOpts::new("server_fn_duration_seconds", "Server fn latency by name"),
+            &["server_fn"],
+        )?;
+        let backend_calls = IntCounterVec::new(
+            Opts::new(
+                "backend_calls_total",
+                "Backend calls by status, `error` if no response arrived",
+            ),
+            &["method", "status"],
+        )?;
+        let backend_duration = HistogramVec::new(
+            HistogramOpts::new("backend_call_duration_seconds", "Backend call latency"),
+            &["method"],
+        )?;
+        let ssr_renders_active =
+            IntGauge::new("ssr_renders_active", "Pages being rendered on the server")?;
+        let votes_cast = IntCounterVec::new(
+            Opts::new("votes_cast_total", "Votes accepted by the backend by topic"),
+            &["topic_id"],
+        )?;
+        let votes_rejected = IntCounterVec::new(
+            Opts::new(
+                "votes_rejected_total",
+                "Votes refused by the backend by status",
+            ),
+            &["status"],
+        )?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_duration.clone()))?;
+        registry.register(Box::new(server_fn_calls.clone()))?;
+        registry.register(Box::new(server_fn_duration.clone()))?;
+        registry.register(Box::new(backend_calls.clone()))?;
+        registry.register(Box::new(backend_duration.clone()))?;
+        registry.register(Box::new(ssr_renders_active.clone()))?;
+        registry.register(Box::new(votes_cast.clone()))?;
+        registry.register(Box::new(votes_rejected.clone()))?;
+
+        Ok(Self {
+            registry,
+            http_requests,
+            http_duration,
+            server_fn_calls,
+            server_fn_duration,
+            backend_calls,
+            backend_duration,
+            ssr_renders_active,
+            votes_cast,
+            votes_rejected,
+        })
+    }
+
+    pub fn backend_call(&self, method: &str, status: Option<u16>, latency: Duration) {
+        let status = status.map_or("error".to_string(), |s| s.to_string());
+        self.backend_calls
+            .with_label_values(&[method, &status])
+            .inc();
+        self.backend_duration
+            .with_label_values(&[method])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn vote_cast(&self, topic_id: &str) {
+        self.votes_cast.with_label_values(&[topic_id]).inc();
+    }
+
+    pub fn vote_rejected(&self, status: u16) {
+        self.votes_rejected
+            .with_label_values(&[&status.to_string()])
+            .inc();
+    }
+
+    fn request(&self, method: &str, route: &str, status: u16, latency: Duration) {
+        let status = status.to_string();
+        self.http_requests
+            .with_label_values(&[method, route, &status])
+            .inc();
+        self.http_duration
+            .with_label_values(&[method, route])
+            .observe(latency.as_secs_f64());
+    }
+
+    fn server_fn_call(&self, name: &str, status: u16, latency: Duration) {
+        self.server_fn_calls
+            .with_label_values(&[name, &status.to_string()])
+            .inc();
+        self.server_fn_duration
+            .with_label_values(&[name])
+            .observe(latency.as_secs_f64());
+    }
+}
+
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("metrics should register"))
+}
+
+/// Counts as an active SSR render until dropped
+///
+/// Kept in the reactive runtime of a render, which is dropped once the page has
+/// been streamed.
+pub struct ActiveRender(());
+
+impl ActiveRender {
+    pub fn start() -> Self {
+        metrics().ssr_renders_active.inc();
+        Self(())
+    }
+}
+
+impl Drop for ActiveRender {
+    fn drop(&mut self) {
+        metrics().ssr_renders_active.dec();
+    }
+}
+
+/// Register `/metrics`
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(scrape));
+}
+
+/// Whether a scrape may see the metrics, `client` is `None` if unknown
+fn is_authorized(token: Option<&str>, authorization: Option<&str>, client: Option<IpAddr>) -> bool {
+    match token {
+        Some(token) => authorization.and_then(|v| v.strip_prefix("Bearer ")) == Some(token),
+        None => client.is_some_and(|ip| ip.is_loopback()),
+    }
+}
+
+async fn scrape(req: HttpRequest) -> HttpResponse {
+    let authorization = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok());
+    if !is_authorized(
+        config().metrics.token.as_deref(),
+        authorization,
+        request_ip(&req),
+    ) {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let encoder = TextEncoder::new();
+    let mut body = vec![];
+    if let Err(e) = encoder.encode(&metrics().registry.gather(), &mut body) {
+        tracing::error!("encode metrics failed: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok()
+        .content_type(encoder.format_type())
+        .body(body)
+}
+
+/// Middleware recording every request
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let method = req.method().to_string();
+        // patterns like `/topic/{id}`, never raw paths, to keep the label set small
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".to_string());
+        let server_fn = server_fn_name(req.path()).map(str::to_string);
+        let started_at = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let result = fut.await;
+            let status = match &result {
+                Ok(res) => res.status().as_u16(),
+                Err(e) => e.as_response_error().status_code().as_u16(),
+            };
+            let latency = started_at.elapsed();
+            metrics().request(&method, &route, status, latency);
+            if let Some(name) = server_fn {
+                metrics().server_fn_call(&name, status, latency);
+            }
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrapes_need_the_token_if_configured() {
+        let remote = Some("192.0.2.1".parse().unwrap());
+        assert!(is_authorized(Some("t"), Some("Bearer t"), remote));
+        assert!(!is_authorized(Some("t"), Some("Bearer x"), remote));
+        assert!(!is_authorized(Some("t"), Some("t"), remote));
+        assert!(!is_authorized(
+            Some("t"),
+            None,
+            Some("127.0.0.1".parse().unwrap())
+        ));
+    }
+
+    #[test]
+    fn scrapes_without_token_only_from_localhost() {
+        assert!(is_authorized(
+            None,
+            None,
+            Some("127.0.0.1".parse().unwrap())
+        ));
+        assert!(is_authorized(None, None, Some("::1".parse().unwrap())));
+        assert!(!is_authorized(
+            None,
+            None,
+            Some("192.0.2.1".parse().unwrap())
+        ));
+        assert!(!is_authorized(None, None, None));
+    }
+}
@@ -0,0 +1,14 @@
+//! Server-only pieces of the frontend, which never reach the client bundle
+
+pub mod assets;
+pub mod config;
+pub mod csrf;
+pub mod health;
+pub mod mail;
+pub mod metrics;
+pub mod oidc;
+pub mod rate_limit;
+pub mod security;
+pub mod shutdown;
+pub mod telemetry;
+pub mod upload;
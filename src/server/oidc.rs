@@ -0,0 +1,500 @@
+//! Single sign-on through an OpenID Connect provider (e.g. the school account).
+//!
+//! Uses the authorization code flow with PKCE. It is enabled when `oidc.issuer` and
+//! `oidc.client_id` are configured (`oidc.client_secret` is optional for public clients).
+//! The verified ID token is exchanged for a backend token, which is handed to the
+//! client in the URL fragment of `/login/oidc` so it never reaches any server log.
+
+use crate::api::{base_url, client, SendExt, Token};
+use crate::component::safe_next;
+use crate::server::config::{config, public_url, Config};
+use actix_web::{cookie::Cookie, http::header, web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use openidconnect::core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata};
+use openidconnect::reqwest::async_http_client;
+use openidconnect::url::form_urlencoded;
+use openidconnect::{
+    AuthorizationCode, ClientId, ClientSecret, CsrfToken, IssuerUrl, Nonce, PkceCodeChallenge,
+    PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const STATE_COOKIE: &str = "oidc_state";
+/// How long a user may take to log in at the provider
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Anyone can start logins, so only this many are remembered and the oldest are dropped,
+/// see also the `oidc` rate limit
+const MAX_PENDING_LOGINS: usize = 10_000;
+
+/// A login that has been sent to the provider and waits for the callback
+struct PendingLogin {
+    pkce_verifier: PkceCodeVerifier,
+    nonce: Nonce,
+    next: String,
+    started_at: Instant,
+}
+
+/// Logins waiting for their callback, keyed by the `state` parameter
+#[derive(Default)]
+struct PendingLogins {
+    logins: HashMap<String, PendingLogin>,
+    /// States in the order their logins started, including those already taken
+    order: VecDeque<(Instant, String)>,
+}
+
+impl PendingLogins {
+    fn insert(&mut self, state: String, login: PendingLogin) {
+        // the expired and the oldest logins are at the front
+        while let Some((started_at, oldest)) = self.order.front() {
+            if started_at.elapsed() < LOGIN_TIMEOUT && self.order.len() < MAX_PENDING_LOGINS {
+                break;
+            }
+            self.logins.remove(oldest);
+            self.order.pop_front();
+        }
+        self.order.push_back((login.started_at, state.clone()));
+        self.logins.insert(state, login);
+    }
+
+    fn take(&mut self, state: &str) -> Option<PendingLogin> {
+        self.logins
+            .remove(state)
+            .filter(|login| login.started_at.elapsed() < LOGIN_TIMEOUT)
+    }
+}
+
+pub struct Oidc {
+    client: CoreClient,
+    pending: Mutex<PendingLogins>,
+}
+
+impl Oidc {
+    /// Discover the configured provider, `None` if SSO is not configured
+    pub async fn from_config(config: &Config) -> anyhow::Result<Option<Self>> {
+        if !config.sso_configured() {
+            return Ok(None);
+        }
+        let (Some(issuer), Some(client_id)) = (&config.oidc.issuer, &config.oidc.client_id) else {
+            return Ok(None);
+        };
+
+        Self::discover(
+            issuer,
+            client_id.clone(),
+            config.oidc.client_secret.clone(),
+            &format!("{}/auth/oidc/callback", public_url()),
+        )
+        .await
+        .map(Some)
+    }
+
+    pub async fn discover(
+        issuer: &str,
+        client_id: String,
+        client_secret: Option<String>,
+        redirect_url: &str,
+    ) -> anyhow::Result<Self> {
+        let issuer = IssuerUrl::new(issuer.to_string()).context("invalid OIDC issuer url")?;
+        let metadata = CoreProviderMetadata::discover_async(issuer, async_http_client)
+            .await
+            .context("OIDC discovery failed")?;
+        let redirect_url =
+            RedirectUrl::new(redirect_url.to_string()).context("invalid OIDC redirect url")?;
+        let client = CoreClient::from_provider_metadata(
+            metadata,
+            ClientId::new(client_id),
+            client_secret.map(ClientSecret::new),
+        )
+        .set_redirect_uri(redirect_url);
+
+        Ok(Self {
+            client,
+            pending: Mutex::default(),
+        })
+    }
+
+    /// Remember a new login, returns the provider URL to send the user to and its `state`
+    fn start_login(&self, next: String) -> (String, String) {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let (auth_url, state, nonce) = self
+            .client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            )
+            .add_scope(Scope::new("email".to_string()))
+            .add_scope(Scope::new("profile".to_string()))
+            .set_pkce_challenge(pkce_challenge)
+            .url();
+
+        self.pending.lock().unwrap().insert(
+            state.secret().clone(),
+            PendingLogin {
+                pkce_verifier,
+                nonce,
+                next,
+                started_at: Instant::now(),
+            },
+        );
+
+        (auth_url.to_string(), state.secret().clone())
+    }
+
+    /// Exchange the code of a pending login, returns the verified ID token and where to go next
+    async fn verify_login(&self, state: &str, code: String) -> anyhow::Result<(String, String)> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap()
+            .take(state)
+            .context("unknown or expired state")?;
+
+        let token_response = self
+            .client
+            .exchange_code(AuthorizationCode::new(code))
+            .set_pkce_verifier(login.pkce_verifier)
+            .request_async(async_http_client)
+            .await
+            .context("code exchange failed")?;
+        let id_token = token_response
+            .id_token()
+            .context("provider did not return an ID token")?;
+        // checks signature, issuer, audience, expiry and nonce
+        id_token
+            .claims(&self.client.id_token_verifier(), &login.nonce)
+            .context("invalid ID token")?;
+
+        Ok((id_token.to_string(), login.next))
+    }
+}
+
+/// Register the login and callback routes
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/auth/oidc/login", web::get().to(login))
+        .route("/auth/oidc/callback", web::get().to(callback));
+}
+
+#[derive(Deserialize)]
+struct LoginQuery {
+    next: Option<String>,
+}
+
+async fn login(oidc: web::Data<Oidc>, query: web::Query<LoginQuery>) -> HttpResponse {
+    let (auth_url, state) = oidc.start_login(safe_next(query.into_inner().next));
+
+    // bind the login to this browser, the callback must come back with the same state
+    let cookie = Cookie::build(STATE_COOKIE, state)
+        .path("/auth/oidc")
+        .http_only(true)
+        .secure(config().cookie.secure)
+        .same_site(config().cookie.same_site.into())
+        .max_age(actix_web::cookie::time::Duration::seconds(
+            LOGIN_TIMEOUT.as_secs() as i64,
+        ))
+        .finish();
+
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, auth_url))
+        .cookie(cookie)
+        .finish()
+}
+
+#[derive(Deserialize)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+async fn callback(
+    req: HttpRequest,
+    oidc: web::Data<Oidc>,
+    query: web::Query<CallbackQuery>,
+) -> HttpResponse {
+    let mut removal = Cookie::named(STATE_COOKIE);
+    removal.set_path("/auth/oidc");
+    removal.make_removal();
+
+    let location = match finish_login(&req, &oidc, query.into_inner()).await {
+        Ok((token, next)) => {
+            let fragment = form_urlencoded::Serializer::new(String::new())
+                .append_pair("access_token", &token.access_token)
+                .append_pair("next", &next)
+                .finish();
+            format!("/login/oidc#{fragment}")
+        }
+        Err(e) => {
+            tracing::warn!("OIDC login failed: {e:#}");
+            let query = form_urlencoded::Serializer::new(String::new())
+                .append_pair("error", "sso-failed")
+                .finish();
+            format!("/login?{query}")
+        }
+    };
+
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, location))
+        .cookie(removal)
+        .finish()
+}
+
+/// Check the callback and exchange the code for a backend token, returns the token and where to go next
+async fn finish_login(
+    req: &HttpRequest,
+    oidc: &Oidc,
+    query: CallbackQuery,
+) -> anyhow::Result<(Token, String)> {
+    if let Some(error) = query.error {
+        anyhow::bail!("provider returned error: {error}");
+    }
+    let code = query.code.context("missing code")?;
+    let state = query.state.context("missing state")?;
+    let cookie_state = req
+        .cookie(STATE_COOKIE)
+        .context("missing state cookie")?
+        .value()
+        .to_string();
+    anyhow::ensure!(cookie_state == state, "state does not match");
+
+    let (id_token, next) = oidc.verify_login(&state, code).await?;
+
+    #[derive(Serialize)]
+    struct OidcTokenRequest {
+        id_token: String,
+    }
+
+    let resp = client()
+        .post(format!("{}/auth/oidc", base_url()))
+        .json(&OidcTokenRequest { id_token })
+        .send_traced()
+        .await?;
+    anyhow::ensure!(
+        resp.status() == reqwest::StatusCode::OK,
+        "backend rejected ID token: {resp:?}"
+    );
+    let token = resp.json::<Token>().await?;
+
+    Ok((token, next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpServer};
+    use openidconnect::core::{
+        CoreIdToken, CoreIdTokenClaims, CoreIdTokenFields, CoreJsonWebKeySet,
+        CoreJwsSigningAlgorithm, CoreResponseType, CoreRsaPrivateSigningKey,
+        CoreSubjectIdentifierType, CoreTokenResponse, CoreTokenType,
+    };
+    use openidconnect::{
+        AccessToken, Audience, AuthUrl, EmptyAdditionalClaims, EmptyAdditionalProviderMetadata,
+        EmptyExtraTokenFields, IssuerUrl, JsonWebKeyId, JsonWebKeySetUrl, PrivateSigningKey,
+        ResponseTypes, StandardClaims, SubjectIdentifier, TokenUrl,
+    };
+    use std::sync::Arc;
+
+    const CLIENT_ID: &str = "vote";
+
+    /// What the mock provider answers, the test fills in the ID token once it knows the nonce
+    struct Provider {
+        issuer: String,
+        key: CoreRsaPrivateSigningKey,
+        id_token: Mutex<Option<String>>,
+    }
+
+    impl Provider {
+        fn id_token(&self, nonce: &str, audience: &str) -> String {
+            let now = chrono::Utc::now();
+            let claims = CoreIdTokenClaims::new(
+                IssuerUrl::new(self.issuer.clone()).unwrap(),
+                vec![Audience::new(audience.to_string())],
+                now + chrono::Duration::minutes(5),
+                now,
+                StandardClaims::new(SubjectIdentifier::new("s1234".to_string())),
+                EmptyAdditionalClaims {},
+            )
+            .set_nonce(Some(Nonce::new(nonce.to_string())));
+
+            CoreIdToken::new(
+                claims,
+                &self.key,
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                None,
+                None,
+            )
+            .unwrap()
+            .to_string()
+        }
+    }
+
+    async fn discovery(provider: web::Data<Provider>) -> HttpResponse {
+        let metadata = CoreProviderMetadata::new(
+            IssuerUrl::new(provider.issuer.clone()).unwrap(),
+            AuthUrl::new(format!("{}/authorize", provider.issuer)).unwrap(),
+            JsonWebKeySetUrl::new(format!("{}/jwks", provider.issuer)).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            EmptyAdditionalProviderMetadata {},
+        )
+        .set_token_endpoint(Some(
+            TokenUrl::new(format!("{}/token", provider.issuer)).unwrap(),
+        ));
+
+        HttpResponse::Ok().json(metadata)
+    }
+
+    async fn jwks(provider: web::Data<Provider>) -> HttpResponse {
+        HttpResponse::Ok().json(CoreJsonWebKeySet::new(vec![provider
+            .key
+            .as_verification_key()]))
+    }
+
+    async fn token(provider: web::Data<Provider>) -> HttpResponse {
+        let id_token = provider.id_token.lock().unwrap().clone();
+        let response = CoreTokenResponse::new(
+            AccessToken::new("provider-access-token".to_string()),
+            CoreTokenType::Bearer,
+            CoreIdTokenFields::new(
+                id_token.map(|token| token.parse().unwrap()),
+                EmptyExtraTokenFields {},
+            ),
+        );
+
+        HttpResponse::Ok().json(response)
+    }
+
+    /// Starts a provider on a free port and discovers it
+    async fn mock_provider() -> (Arc<Provider>, Oidc) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let pem = openssl::rsa::Rsa::generate(2048)
+            .unwrap()
+            .private_key_to_pem()
+            .unwrap();
+        let provider = Arc::new(Provider {
+            issuer: issuer.clone(),
+            key: CoreRsaPrivateSigningKey::from_pem(
+                std::str::from_utf8(&pem).unwrap(),
+                Some(JsonWebKeyId::new("test".to_string())),
+            )
+            .unwrap(),
+            id_token: Mutex::new(None),
+        });
+
+        let data = web::Data::from(provider.clone());
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(data.clone())
+                .route(
+                    "/.well-known/openid-configuration",
+                    web::get().to(discovery),
+                )
+                .route("/jwks", web::get().to(jwks))
+                .route("/token", web::post().to(token))
+        })
+        .workers(1)
+        .listen(listener)
+        .unwrap()
+        .run();
+        actix_web::rt::spawn(server);
+
+        let oidc = Oidc::discover(
+            &issuer,
+            CLIENT_ID.to_string(),
+            None,
+            "http://localhost:3000/auth/oidc/callback",
+        )
+        .await
+        .unwrap();
+
+        (provider, oidc)
+    }
+
+    /// The `nonce` the provider would see in the authorization URL
+    fn nonce_of(auth_url: &str) -> String {
+        let url = openidconnect::url::Url::parse(auth_url).unwrap();
+        let nonce = url.query_pairs().find(|(key, _)| key == "nonce").unwrap();
+        nonce.1.into_owned()
+    }
+
+    #[actix_web::test]
+    async fn login_with_a_valid_id_token() {
+        let (provider, oidc) = mock_provider().await;
+        let (auth_url, state) = oidc.start_login("/topic/1".to_string());
+        assert!(auth_url.starts_with(&format!("{}/authorize?", provider.issuer)));
+        assert!(auth_url.contains("code_challenge="));
+
+        let id_token = provider.id_token(&nonce_of(&auth_url), CLIENT_ID);
+        *provider.id_token.lock().unwrap() = Some(id_token.clone());
+
+        let (verified, next) = oidc.verify_login(&state, "code".to_string()).await.unwrap();
+        assert_eq!(verified, id_token);
+        assert_eq!(next, "/topic/1");
+        // a state can only be used once
+        assert!(oidc.verify_login(&state, "code".to_string()).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn reject_id_tokens_for_another_login_or_client() {
+        let (provider, oidc) = mock_provider().await;
+
+        let (_, state) = oidc.start_login("/".to_string());
+        *provider.id_token.lock().unwrap() = Some(provider.id_token("other-nonce", CLIENT_ID));
+        assert!(oidc.verify_login(&state, "code".to_string()).await.is_err());
+
+        let (auth_url, state) = oidc.start_login("/".to_string());
+        *provider.id_token.lock().unwrap() =
+            Some(provider.id_token(&nonce_of(&auth_url), "other-client"));
+        assert!(oidc.verify_login(&state, "code".to_string()).await.is_err());
+
+        assert!(oidc
+            .verify_login("unknown", "code".to_string())
+            .await
+            .is_err());
+    }
+
+    #[actix_web::test]
+    async fn forget_the_oldest_logins_when_full() {
+        let (_, oidc) = mock_provider().await;
+        let (_, first) = oidc.start_login("/".to_string());
+        for _ in 0..MAX_PENDING_LOGINS {
+            oidc.start_login("/".to_string());
+        }
+
+        let pending = oidc.pending.lock().unwrap();
+        assert_eq!(pending.logins.len(), MAX_PENDING_LOGINS);
+        assert!(!pending.logins.contains_key(&first));
+    }
+
+    #[test]
+    fn forget_expired_logins_at_once() {
+        let login = |started_at| PendingLogin {
+            pkce_verifier: PkceCodeVerifier::new("verifier".to_string()),
+            nonce: Nonce::new("nonce".to_string()),
+            next: "/".to_string(),
+            started_at,
+        };
+        let Some(expired) = Instant::now().checked_sub(LOGIN_TIMEOUT) else {
+            return;
+        };
+        let mut pending = PendingLogins::default();
+        for i in 0..3 {
+            pending.insert(format!("old-{i}"), login(expired));
+        }
+        pending.insert("taken".to_string(), login(Instant::now()));
+        assert!(pending.take("taken").is_some());
+        assert!(pending.take("old-0").is_none());
+
+        pending.insert("new".to_string(), login(Instant::now()));
+        assert_eq!(pending.logins.len(), 1);
+        // the taken login is only forgotten once it would have expired
+        assert_eq!(pending.order.len(), 2);
+    }
+}
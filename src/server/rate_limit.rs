@@ -0,0 +1,608 @@
+//! Token-bucket rate limiting of server fns, uploads and school logins.
+//!
+//! Every limited server fn has a bucket per client IP and, if the call carries a
+//! `token`, one per user the token belongs to, so that minting another token does not
+//! give a fresh budget. A username (logging in / signing up) only gets a bucket
+//! together with the IP, so that nobody can lock someone else out by guessing their
+//! password. Behind `server.trusted_proxies` the client IP is taken from
+//! `X-Forwarded-For`. Limits are set per server fn name in the
+//! `[rate_limits]` config, e.g. `create_access_token = "5/60"` allows a burst of 5
+//! logins refilled over a minute. Calls over the limit are answered with 429 and
+//! `Retry-After`.
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, Method},
+    web, Error, HttpResponse,
+};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    future::{ready, Future, Ready},
+    net::IpAddr,
+    pin::Pin,
+    rc::Rc,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::server::config::config;
+
+/// Limits used unless overridden in the config
+const DEFAULT_LIMITS: &[(&str, &str)] = &[
+    ("create_access_token", "5/60"),
+    ("signup", "3/3600"),
+    ("check_availability", "10/60"),
+    ("create_vote", "10/60"),
+    ("create_comment", "5/60"),
+    ("set_reaction", "30/60"),
+    ("report_comment", "5/600"),
+    ("request_password_reset", "3/3600"),
+    ("request_email_verification", "3/3600"),
+    // not server fns, see `server::upload` and `server::oidc`
+    ("upload", "20/600"),
+    ("oidc", "10/600"),
+];
+
+/// Stop remembering idle clients once there are this many buckets
+const MAX_BUCKETS: usize = 10_000;
+/// How long the owner of a token is remembered before asking the backend again
+const ACCOUNT_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Allows `burst` calls at once, refilled evenly over `period`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Limit {
+    burst: f64,
+    period: Duration,
+}
+
+impl Limit {
+    pub fn new(burst: u32, period: Duration) -> Self {
+        Self {
+            burst: burst.max(1) as f64,
+            period,
+        }
+    }
+
+    fn refill_rate(&self) -> f64 {
+        self.burst / self.period.as_secs_f64()
+    }
+}
+
+impl FromStr for Limit {
+    type Err = anyhow::Error;
+
+    /// Parses `<burst>/<seconds>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (burst, seconds) = s
+            .split_once('/')
+            .with_context(|| format!("rate limit {s:?} should look like <burst>/<seconds>"))?;
+        let burst = burst.trim().parse().context("invalid burst")?;
+        let seconds: u64 = seconds.trim().parse().context("invalid seconds")?;
+        anyhow::ensure!(seconds > 0, "rate limit period should not be zero");
+
+        Ok(Self::new(burst, Duration::from_secs(seconds)))
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.burst, self.period.as_secs())
+    }
+}
+
+impl TryFrom<String> for Limit {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Limit> for String {
+    fn from(limit: Limit) -> Self {
+        limit.to_string()
+    }
+}
+
+/// The default limits with `overrides` applied
+pub fn effective_limits(overrides: &BTreeMap<String, Limit>) -> BTreeMap<String, Limit> {
+    let mut limits = DEFAULT_LIMITS
+        .iter()
+        .map(|(name, limit)| (name.to_string(), limit.parse().unwrap()))
+        .collect::<BTreeMap<_, _>>();
+    limits.extend(overrides.iter().map(|(name, limit)| (name.clone(), *limit)));
+
+    limits
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(limit: &Limit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, limit: &Limit, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.refill_rate()).min(limit.burst);
+        self.updated = now;
+    }
+
+    /// Time until the next call is allowed
+    fn wait(&self, limit: &Limit) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / limit.refill_rate())
+        }
+    }
+}
+
+pub struct RateLimiter {
+    limits: HashMap<String, Limit>,
+    /// Keyed by server fn name and client, e.g. `("signup", "ip:127.0.0.1")`
+    buckets: Mutex<HashMap<(String, String), Bucket>>,
+    /// Username of each recently seen token and when it was looked up
+    accounts: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: HashMap<String, Limit>) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::new(HashMap::new()),
+            accounts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Default limits, overridden by the `[rate_limits]` config
+    pub fn with_overrides(overrides: &BTreeMap<String, Limit>) -> Self {
+        Self::new(effective_limits(overrides).into_iter().collect())
+    }
+
+    pub fn is_limited(&self, server_fn: &str) -> bool {
+        self.limits.contains_key(server_fn)
+    }
+
+    /// Takes one token from every bucket of the client, or tells how long to wait
+    ///
+    /// Nothing is taken if any bucket is empty.
+    pub fn acquire(&self, server_fn: &str, clients: &[String]) -> Result<(), Duration> {
+        let Some(limit) = self.limits.get(server_fn) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_BUCKETS {
+            buckets.retain(|(name, _), bucket| match self.limits.get(name) {
+                Some(limit) => {
+                    bucket.refill(limit, now);
+                    bucket.tokens < limit.burst
+                }
+                None => false,
+            });
+        }
+
+        let mut wait = Duration::ZERO;
+        for client in clients {
+            let bucket = buckets
+                .entry((server_fn.to_string(), client.clone()))
+                .or_insert_with(|| Bucket::full(limit, now));
+            bucket.refill(limit, now);
+            wait = wait.max(bucket.wait(limit));
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for client in clients {
+            if let Some(bucket) = buckets.get_mut(&(server_fn.to_string(), client.clone())) {
+                bucket.tokens -= 1.0;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The account a [`User::Token`] belongs to, asking the backend unless it was seen
+    /// recently. Tokens the backend doesn't know stay as they are and only get the IP
+    /// bucket, the call fails anyway.
+    pub async fn resolve(&self, user: User) -> User {
+        let User::Token(token) = user else {
+            return user;
+        };
+        if let Some(username) = self.cached_account(&token, Instant::now()) {
+            return User::Account(username);
+        }
+        let Ok(me) = crate::api::get_me(token.clone()).await else {
+            return User::Token(token);
+        };
+
+        let now = Instant::now();
+        let mut accounts = self.accounts.lock().unwrap();
+        if accounts.len() >= MAX_BUCKETS {
+            accounts.retain(|_, (_, seen)| now.duration_since(*seen) < ACCOUNT_TTL);
+            if accounts.len() >= MAX_BUCKETS {
+                accounts.clear();
+            }
+        }
+        accounts.insert(token, (me.username.clone(), now));
+
+        User::Account(me.username)
+    }
+
+    fn cached_account(&self, token: &str, now: Instant) -> Option<String> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .get(token)
+            .filter(|(_, seen)| now.duration_since(*seen) < ACCOUNT_TTL)
+            .map(|(username, _)| username.clone())
+    }
+
+    /// How long the client has to wait before calling the server fn again
+    pub fn cooldown(&self, server_fn: &str, clients: &[String]) -> Duration {
+        let Some(limit) = self.limits.get(server_fn) else {
+            return Duration::ZERO;
+        };
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let mut wait = Duration::ZERO;
+        for client in clients {
+            if let Some(bucket) = buckets.get_mut(&(server_fn.to_string(), client.clone())) {
+                bucket.refill(limit, now);
+                wait = wait.max(bucket.wait(limit));
+            }
+        }
+
+        wait
+    }
+}
+
+/// Server fn name of a request path, the URL is the name followed by a hash
+///
+/// `None` unless a server fn is registered there, anyone can make up paths under `/api`.
+pub fn server_fn_name(path: &str) -> Option<&str> {
+    let url = path.strip_prefix("/api/")?;
+    leptos::leptos_server::server_fn_by_path(url)?;
+    Some(url.trim_end_matches(|c: char| c.is_ascii_digit()))
+}
+
+/// Name of the limit a request counts against, server fn calls by their name and
+/// starting a login with the school account as `oidc`
+fn limit_name<'a>(method: &Method, path: &'a str) -> Option<&'a str> {
+    match (method, path) {
+        (&Method::GET, "/auth/oidc/login") => Some("oidc"),
+        (&Method::POST, path) => server_fn_name(path),
+        _ => None,
+    }
+}
+
+/// The address a request comes from. Behind trusted proxies this is the last address in
+/// `X-Forwarded-For` that was not added by another trusted proxy, anything before it
+/// is made up by the client at will.
+pub fn client_ip(peer: IpAddr, forwarded_for: Option<&str>, trusted: &[IpAddr]) -> IpAddr {
+    let mut ip = peer;
+    for hop in forwarded_for.into_iter().flat_map(|v| v.rsplit(',')) {
+        if !trusted.contains(&ip) {
+            break;
+        }
+        match hop.trim().parse() {
+            Ok(hop) => ip = hop,
+            Err(_) => break,
+        }
+    }
+
+    ip
+}
+
+/// [`client_ip`] of a request, `None` in tests without a peer
+pub fn request_ip(req: &actix_web::HttpRequest) -> Option<IpAddr> {
+    let forwarded_for = req
+        .headers()
+        .get_all("x-forwarded-for")
+        .filter_map(|v| v.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+    let forwarded_for = (!forwarded_for.is_empty()).then_some(forwarded_for.as_str());
+
+    Some(client_ip(
+        req.peer_addr()?.ip(),
+        forwarded_for,
+        &config().server.trusted_proxies,
+    ))
+}
+
+/// Bucket key of a client IP
+pub fn ip_client(req: &actix_web::HttpRequest) -> Option<String> {
+    request_ip(req).map(|ip| format!("ip:{ip}"))
+}
+
+/// Who a server fn call is made for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum User {
+    /// Not yet [resolved](RateLimiter::resolve) to its account
+    Token(String),
+    /// Username of a logged in user
+    Account(String),
+    /// Trying to log in / sign up
+    Username(String),
+}
+
+/// Bucket keys of a call, the client IP and the user
+pub fn clients(ip: Option<String>, user: Option<&User>) -> Vec<String> {
+    let user = match (user, &ip) {
+        (Some(User::Account(username)), _) => Some(format!("user:{username}")),
+        (Some(User::Username(username)), Some(ip)) => Some(format!("login:{ip}:{username}")),
+        _ => None,
+    };
+
+    ip.into_iter().chain(user).collect()
+}
+
+/// Finds the user a server fn call is made for in its URL encoded arguments
+fn user_of(body: &[u8]) -> Option<User> {
+    let args = web::Query::<Vec<(String, String)>>::from_query(std::str::from_utf8(body).ok()?)
+        .ok()?
+        .into_inner();
+    let find = |key: &dyn Fn(&str) -> bool| {
+        args.iter()
+            .find(|(k, v)| key(k) && !v.is_empty())
+            .map(|(_, v)| v.clone())
+    };
+
+    find(&|k| k == "token")
+        .map(User::Token)
+        .or_else(|| find(&|k| k.ends_with("[username]")).map(User::Username))
+}
+
+/// The answer to a call over the limit, which may be retried after `wait`
+pub fn too_many_requests(wait: Duration) -> HttpResponse {
+    let retry_after = wait.as_secs_f64().ceil() as u64;
+    // not the clients, user keys may be tokens
+    tracing::warn!(retry_after, "rate limited");
+    HttpResponse::TooManyRequests()
+        .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+        .body(format!("Too many requests, retry in {retry_after} seconds"))
+}
+
+/// Middleware enforcing the limits of a [`RateLimiter`]
+#[derive(Clone)]
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimit {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            limiter: self.limiter.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            let name = limit_name(req.method(), req.path())
+                .filter(|name| limiter.is_limited(name))
+                .map(str::to_string);
+            let Some(name) = name else {
+                return Ok(service.call(req).await?.map_into_left_body());
+            };
+
+            // the user is only known from the arguments, so read the body and put it back
+            let body = req.extract::<web::Bytes>().await?;
+            let user = match user_of(&body) {
+                Some(user) => Some(limiter.resolve(user).await),
+                None => None,
+            };
+            let (_, mut payload) = actix_http::h1::Payload::create(true);
+            payload.unread_data(body);
+            req.set_payload(payload.into());
+
+            let clients = clients(ip_client(req.request()), user.as_ref());
+            if let Err(wait) = limiter.acquire(&name, &clients) {
+                let resp = too_many_requests(wait);
+                return Ok(req.into_response(resp).map_into_right_body());
+            }
+
+            Ok(service.call(req).await?.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(limit: &str) -> RateLimiter {
+        RateLimiter::new(HashMap::from([(
+            "create_vote".to_string(),
+            limit.parse().unwrap(),
+        )]))
+    }
+
+    #[test]
+    fn parse_limits() {
+        let limit = "5/60".parse::<Limit>().unwrap();
+        assert_eq!(limit.to_string(), "5/60");
+        assert_eq!(limit.refill_rate(), 5.0 / 60.0);
+        assert_eq!(" 3 / 3600 ".parse::<Limit>().unwrap().to_string(), "3/3600");
+
+        for invalid in ["5", "5/0", "a/60", "5/a", ""] {
+            assert!(invalid.parse::<Limit>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let overrides = BTreeMap::from([("signup".to_string(), "10/60".parse().unwrap())]);
+        let limits = effective_limits(&overrides);
+        assert_eq!(limits["signup"].to_string(), "10/60");
+        assert_eq!(limits["create_access_token"].to_string(), "5/60");
+    }
+
+    #[test]
+    fn burst_then_wait() {
+        let limiter = limiter("2/60");
+        let clients = ["ip:127.0.0.1".to_string()];
+        assert!(limiter.acquire("create_vote", &clients).is_ok());
+        assert!(limiter.acquire("create_vote", &clients).is_ok());
+
+        let wait = limiter.acquire("create_vote", &clients).unwrap_err();
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+        assert!(limiter.cooldown("create_vote", &clients) > Duration::ZERO);
+        // unlimited server fns always pass
+        assert!(limiter.acquire("get_topics", &clients).is_ok());
+    }
+
+    #[test]
+    fn every_bucket_must_have_a_token() {
+        let limiter = limiter("1/60");
+        let first = ["ip:10.0.0.1".to_string(), "user:a".to_string()];
+        let second = ["ip:10.0.0.2".to_string(), "user:a".to_string()];
+        assert!(limiter.acquire("create_vote", &first).is_ok());
+        // same user from another IP
+        assert!(limiter.acquire("create_vote", &second).is_err());
+        // nothing was taken from the other IP by the rejected call
+        assert!(limiter
+            .acquire("create_vote", &["ip:10.0.0.2".to_string()])
+            .is_ok());
+    }
+
+    #[test]
+    fn usernames_are_limited_per_ip() {
+        let user = User::Username("alice".to_string());
+        let attacker = clients(Some("ip:10.0.0.1".to_string()), Some(&user));
+        let owner = clients(Some("ip:10.0.0.2".to_string()), Some(&user));
+        assert_ne!(attacker[1], owner[1]);
+
+        let account = User::Account("bob".to_string());
+        assert_eq!(
+            clients(Some("ip:10.0.0.1".to_string()), Some(&account))[1],
+            clients(Some("ip:10.0.0.2".to_string()), Some(&account))[1],
+        );
+        assert_eq!(clients(None, Some(&user)), Vec::<String>::new());
+        // unknown tokens only count against the IP
+        let token = User::Token("t".to_string());
+        assert_eq!(
+            clients(Some("ip:10.0.0.1".to_string()), Some(&token)),
+            ["ip:10.0.0.1"]
+        );
+    }
+
+    #[actix_web::test]
+    async fn tokens_of_one_account_share_a_bucket() {
+        let limiter = limiter("1/60");
+        let now = Instant::now();
+        for token in ["first", "second"] {
+            limiter
+                .accounts
+                .lock()
+                .unwrap()
+                .insert(token.to_string(), ("bob".to_string(), now));
+        }
+
+        let first = limiter.resolve(User::Token("first".to_string())).await;
+        let second = limiter.resolve(User::Token("second".to_string())).await;
+        assert_eq!(first, User::Account("bob".to_string()));
+        assert!(limiter
+            .acquire("create_vote", &clients(None, Some(&first)))
+            .is_ok());
+        // a freshly minted token of the same user
+        assert!(limiter
+            .acquire("create_vote", &clients(None, Some(&second)))
+            .is_err());
+    }
+
+    #[test]
+    fn find_the_user_in_arguments() {
+        assert_eq!(
+            user_of(b"token=abc&input%5Bcontent%5D=hi"),
+            Some(User::Token("abc".to_string()))
+        );
+        assert_eq!(
+            user_of(b"input%5Busername%5D=alice&input%5Bpassword%5D=pw"),
+            Some(User::Username("alice".to_string()))
+        );
+        assert_eq!(user_of(b"token=&input%5Bid%5D=1"), None);
+    }
+
+    #[test]
+    fn school_logins_are_limited() {
+        assert_eq!(limit_name(&Method::GET, "/auth/oidc/login"), Some("oidc"));
+        assert_eq!(limit_name(&Method::GET, "/auth/oidc/callback"), None);
+        assert_eq!(limit_name(&Method::GET, "/api/get_topics123"), None);
+        assert!(effective_limits(&BTreeMap::new()).contains_key("oidc"));
+    }
+
+    #[test]
+    fn client_ip_behind_trusted_proxies() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let proxy = ip("127.0.0.1");
+        let trusted = [proxy, ip("10.0.0.1")];
+
+        // not behind a proxy, the header is made up
+        assert_eq!(
+            client_ip(ip("1.2.3.4"), Some("5.6.7.8"), &trusted),
+            ip("1.2.3.4")
+        );
+        assert_eq!(client_ip(proxy, None, &trusted), proxy);
+        assert_eq!(client_ip(proxy, Some("1.2.3.4"), &trusted), ip("1.2.3.4"));
+        // the client prepended a fake address, the proxies appended the real one
+        assert_eq!(
+            client_ip(proxy, Some("6.6.6.6, 1.2.3.4, 10.0.0.1"), &trusted),
+            ip("1.2.3.4")
+        );
+        assert_eq!(client_ip(proxy, Some("garbage"), &trusted), proxy);
+        assert_eq!(client_ip(proxy, Some("1.2.3.4"), &[]), proxy);
+    }
+}
@@ -0,0 +1,308 @@
+//! Security headers sent with every response.
+//!
+//! The Content-Security-Policy only allows this site: styles and scripts are served
+//! from `/pkg`, and `'wasm-unsafe-eval'` is needed for WASM. Leptos 0.3 renders
+//! inline scripts (hydration, resources, streamed `<Suspense/>`) without a nonce, so
+//! [`ScriptNonce`] adds a fresh nonce to every `<script` of an HTML response and puts
+//! it in place of `{nonce}` in the policy. Everything else a page renders is escaped
+//! by Leptos, so the only `<script` tags in a response are the ones it wrote.
+
+use crate::server::config::Config;
+use actix_web::{
+    body::{BodyStream, BoxBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderValue},
+    middleware::DefaultHeaders,
+    web::Bytes,
+    Error,
+};
+use futures::stream;
+use leptos::LeptosOptions;
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+/// Replaced by the nonce of each response in `security.content_security_policy`
+pub const NONCE_PLACEHOLDER: &str = "{nonce}";
+
+/// The configured policy, plus the live reload websocket of `cargo leptos watch`
+pub fn content_security_policy(config: &Config, leptos_options: &LeptosOptions) -> String {
+    let csp = config.security.content_security_policy.clone();
+    if std::env::var("LEPTOS_WATCH").is_err() {
+        return csp;
+    }
+
+    let reload = format!(
+        "ws://{}:{}",
+        leptos_options.site_addr.ip(),
+        leptos_options.reload_port
+    );
+    match csp.find("connect-src") {
+        Some(i) => {
+            let end = csp[i..].find(';').map_or(csp.len(), |j| i + j);
+            format!("{} {reload}{}", &csp[..end], &csp[end..])
+        }
+        None => format!("{csp}; connect-src 'self' {reload}"),
+    }
+}
+
+/// Default headers for every response, handlers may still set their own
+///
+/// The Content-Security-Policy is set by [`ScriptNonce`].
+pub fn headers(config: &Config) -> DefaultHeaders {
+    let mut headers = DefaultHeaders::new()
+        .add(("X-Frame-Options", "DENY"))
+        .add(("X-Content-Type-Options", "nosniff"))
+        .add(("Referrer-Policy", "strict-origin-when-cross-origin"))
+        .add((
+            "Permissions-Policy",
+            "camera=(), microphone=(), geolocation=(), payment=(), usb=(), interest-cohort=()",
+        ));
+    // browsers ignore HSTS over plain HTTP, and it would pin localhost during development
+    if config.server.public_url.starts_with("https://") && config.security.hsts_max_age_secs > 0 {
+        headers = headers.add((
+            "Strict-Transport-Security",
+            format!(
+                "max-age={}; includeSubDomains",
+                config.security.hsts_max_age_secs
+            ),
+        ));
+    }
+
+    headers
+}
+
+/// Adds ` nonce=<nonce>` to every `<script` tag of a body arriving in chunks
+struct NonceInserter {
+    attribute: Vec<u8>,
+    /// The end of the last chunk if it might be the start of a tag
+    carry: Vec<u8>,
+}
+
+impl NonceInserter {
+    const TAG: &'static [u8] = b"<script";
+
+    /// `nonce` is alphanumeric, so it needs no quotes
+    fn new(nonce: &str) -> Self {
+        Self {
+            attribute: format!(" nonce={nonce}").into_bytes(),
+            carry: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) -> Bytes {
+        let mut input = std::mem::take(&mut self.carry);
+        input.extend_from_slice(chunk);
+
+        let mut output = Vec::with_capacity(input.len() + self.attribute.len());
+        let mut rest = input.as_slice();
+        while let Some(i) = rest.windows(Self::TAG.len()).position(|w| w == Self::TAG) {
+            let end = i + Self::TAG.len();
+            output.extend_from_slice(&rest[..end]);
+            output.extend_from_slice(&self.attribute);
+            rest = &rest[end..];
+        }
+        let keep = (1..Self::TAG.len())
+            .rev()
+            .find(|&n| rest.ends_with(&Self::TAG[..n]))
+            .unwrap_or(0);
+        output.extend_from_slice(&rest[..rest.len() - keep]);
+        self.carry = rest[rest.len() - keep..].to_vec();
+
+        output.into()
+    }
+
+    fn finish(self) -> Bytes {
+        self.carry.into()
+    }
+}
+
+/// Middleware setting the Content-Security-Policy with a nonce for inline scripts
+#[derive(Clone)]
+pub struct ScriptNonce {
+    policy: Arc<str>,
+}
+
+impl ScriptNonce {
+    /// `policy` may refer to the nonce as `{nonce}`, e.g. `script-src 'nonce-{nonce}'`
+    pub fn new(policy: impl Into<Arc<str>>) -> Self {
+        Self {
+            policy: policy.into(),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ScriptNonce
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = ScriptNonceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ScriptNonceMiddleware {
+            service: Rc::new(service),
+            policy: self.policy.clone(),
+        }))
+    }
+}
+
+pub struct ScriptNonceMiddleware<S> {
+    service: Rc<S>,
+    policy: Arc<str>,
+}
+
+impl<S, B> Service<ServiceRequest> for ScriptNonceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        let policy = self.policy.replace(NONCE_PLACEHOLDER, &nonce);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            // validated at startup, the nonce is hex
+            let policy = HeaderValue::from_str(&policy).expect("policy should be a valid header");
+            res.headers_mut()
+                .insert(header::CONTENT_SECURITY_POLICY, policy);
+
+            let is_html = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("text/html"));
+            if !is_html {
+                return Ok(res.map_into_boxed_body());
+            }
+
+            Ok(res.map_body(|_, body| {
+                let state = (Box::pin(body), Some(NonceInserter::new(&nonce)));
+                let body = stream::unfold(state, |(mut body, mut inserter)| async move {
+                    // gone once the body has ended
+                    inserter.as_ref()?;
+                    let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await;
+                    let chunk: Result<_, Box<dyn std::error::Error>> = match chunk {
+                        Some(Ok(chunk)) => Ok(inserter.as_mut()?.push(&chunk)),
+                        Some(Err(e)) => Err(e.into()),
+                        // a partial `<script` left at the very end
+                        None => Ok(inserter.take()?.finish()),
+                    };
+                    Some((chunk, (body, inserter)))
+                });
+
+                BoxBody::new(BodyStream::new(body))
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(chunks: &[&str]) -> String {
+        let mut inserter = NonceInserter::new("abc");
+        let mut output = chunks
+            .iter()
+            .map(|chunk| inserter.push(chunk.as_bytes()))
+            .collect::<Vec<_>>();
+        output.push(inserter.finish());
+
+        output
+            .iter()
+            .map(|chunk| std::str::from_utf8(chunk).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn add_nonce_to_every_script() {
+        assert_eq!(
+            insert(&[r#"<p>a</p><script type="module">x</script><script>y</script>"#]),
+            r#"<p>a</p><script nonce=abc type="module">x</script><script nonce=abc>y</script>"#
+        );
+        assert_eq!(insert(&["<p>&lt;script&gt;</p>"]), "<p>&lt;script&gt;</p>");
+    }
+
+    #[test]
+    fn add_nonce_to_scripts_split_over_chunks() {
+        assert_eq!(
+            insert(&["<p>a</p><scr", "ipt>x</script><", "script>y</script>"]),
+            "<p>a</p><script nonce=abc>x</script><script nonce=abc>y</script>"
+        );
+        assert_eq!(
+            insert(&["<", "s", "c", "r", "i", "p", "t>"]),
+            "<script nonce=abc>"
+        );
+        assert_eq!(insert(&["a <", "b"]), "a <b");
+        assert_eq!(insert(&["ends with <scr"]), "ends with <scr");
+    }
+
+    #[actix_web::test]
+    async fn nonce_in_policy_and_html() {
+        use actix_web::{test, web, App, HttpResponse};
+
+        let app = test::init_service(
+            App::new()
+                .wrap(ScriptNonce::new("script-src 'nonce-{nonce}'"))
+                .route(
+                    "/",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .content_type("text/html; charset=utf-8")
+                            .body("<script>hydrate()</script>")
+                    }),
+                )
+                .route(
+                    "/data.js",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .content_type("text/javascript")
+                            .body("'<script>'")
+                    }),
+                ),
+        )
+        .await;
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let policy = res
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let nonce = policy
+            .strip_prefix("script-src 'nonce-")
+            .and_then(|rest| rest.strip_suffix('\''))
+            .unwrap()
+            .to_string();
+        assert_eq!(nonce.len(), 32);
+        let body = test::read_body(res).await;
+        assert_eq!(body, format!("<script nonce={nonce}>hydrate()</script>"));
+
+        // a new nonce for every response, and only HTML is changed
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/data.js").to_request()).await;
+        assert_ne!(
+            res.headers().get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            policy.as_str()
+        );
+        assert_eq!(test::read_body(res).await, "'<script>'");
+    }
+}
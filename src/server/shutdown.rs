@@ -0,0 +1,188 @@
+//! Graceful shutdown on SIGTERM or Ctrl-C.
+//!
+//! `/readyz` fails right away, but the server keeps serving for
+//! `server.shutdown_delay_secs` so load balancers stop sending new requests. Then it
+//! stops accepting connections and in-flight requests get `server.shutdown_grace_secs`
+//! to finish. Server fn calls still running after that are logged with their request
+//! id before they are aborted, so a vote that may not have reached the backend can be
+//! followed up.
+
+use crate::server::config::ServerConfig;
+use crate::server::rate_limit::server_fn_name;
+use crate::server::telemetry::current_request_id;
+use actix_web::{
+    dev::{forward_ready, ServerHandle, Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use std::{
+    collections::HashMap,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static ABORTED: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether a shutdown has begun, `/readyz` fails from then on
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Wait for SIGTERM or Ctrl-C, then stop `server` gracefully
+///
+/// The server must be built with `disable_signals`.
+pub async fn on_signal(server: ServerHandle, config: &ServerConfig) {
+    wait_for_signal().await;
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    tracing::info!(
+        delay_secs = config.shutdown_delay_secs,
+        "shutting down, reporting not ready"
+    );
+    tokio::time::sleep(Duration::from_secs(config.shutdown_delay_secs)).await;
+
+    // Draining is done here instead of by a graceful `stop`: its workers can see the
+    // accept thread go away before the stop message, and then drop every connection.
+    // A paused server keeps serving the connections it has.
+    server.pause().await;
+    tracing::info!(
+        in_flight = in_flight().lock().unwrap().len(),
+        grace_secs = config.shutdown_grace_secs,
+        "no longer accepting connections, draining server fn calls"
+    );
+    let deadline = Instant::now() + Duration::from_secs(config.shutdown_grace_secs);
+    while !in_flight().lock().unwrap().is_empty() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let drained = {
+        let calls = in_flight().lock().unwrap();
+        for call in calls.values() {
+            tracing::error!(
+                server_fn = %call.server_fn,
+                request_id = call.request_id.as_deref().unwrap_or("-"),
+                elapsed_ms = call.started_at.elapsed().as_millis() as u64,
+                "server fn call aborted by shutdown"
+            );
+        }
+        ABORTED.store(calls.len(), Ordering::SeqCst);
+        calls.is_empty()
+    };
+    server.stop(drained).await;
+}
+
+/// Log how the server stopped, once it has
+pub fn report() {
+    let aborted = ABORTED.load(Ordering::SeqCst);
+    if aborted > 0 {
+        tracing::error!(aborted, "stopped before all server fn calls finished");
+    } else {
+        tracing::info!("stopped");
+    }
+}
+
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate =
+            signal(SignalKind::terminate()).expect("SIGTERM handler should install");
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+struct Call {
+    server_fn: String,
+    request_id: Option<String>,
+    started_at: Instant,
+}
+
+/// Server fn calls being handled, by a sequence number
+fn in_flight() -> &'static Mutex<HashMap<u64, Call>> {
+    static IN_FLIGHT: OnceLock<Mutex<HashMap<u64, Call>>> = OnceLock::new();
+    IN_FLIGHT.get_or_init(Default::default)
+}
+
+/// Keeps a call in [`in_flight`] until dropped
+struct CallGuard(u64);
+
+impl CallGuard {
+    fn start(server_fn: String) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let call = Call {
+            server_fn,
+            request_id: current_request_id(),
+            started_at: Instant::now(),
+        };
+        in_flight().lock().unwrap().insert(id, call);
+
+        Self(id)
+    }
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        in_flight().lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Middleware counting in-flight server fn calls
+pub struct TrackInFlight;
+
+impl<S, B> Transform<S, ServiceRequest> for TrackInFlight
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TrackInFlightMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TrackInFlightMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct TrackInFlightMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TrackInFlightMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let call = server_fn_name(req.path()).map(|name| CallGuard::start(name.to_string()));
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let _call = call;
+            fut.await
+        })
+    }
+}
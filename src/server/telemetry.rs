@@ -0,0 +1,258 @@
+//! Logging and request tracing.
+//!
+//! Every request gets an id (taken from a well-formed `X-Request-Id` or generated),
+//! which is echoed in the response, attached to its span and passed on to the backend.
+//! Log lines are JSON by default, and tokens and passwords are masked before they are
+//! written.
+
+use crate::server::config::{LogConfig, LogFormat};
+use crate::server::rate_limit::server_fn_name;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error,
+};
+use regex::Regex;
+use std::{
+    borrow::Cow,
+    future::{ready, Future, Ready},
+    io,
+    pin::Pin,
+    rc::Rc,
+    sync::OnceLock,
+    time::Instant,
+};
+use tracing::Instrument;
+use tracing_subscriber::{fmt::MakeWriter, EnvFilter};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being handled, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Install the global subscriber, `config` is validated at startup
+pub fn init(config: &LogConfig) {
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&config.level))
+        .with_writer(RedactingWriter);
+    match config.format {
+        LogFormat::Json => builder.json().flatten_event(true).init(),
+        LogFormat::Text => builder.init(),
+    }
+}
+
+/// Masks credentials in a log line
+///
+/// Covers `Bearer` tokens and token / password fields in URL queries, forms, JSON
+/// and `Debug` output.
+pub fn redact(line: &str) -> Cow<'_, str> {
+    static PATTERNS: OnceLock<(Regex, Regex)> = OnceLock::new();
+    let (bearer, field) = PATTERNS.get_or_init(|| {
+        const FIELDS: &str = "access_token|refresh_token|id_token|token|password|new_password|old_password|client_secret";
+        let bearer = Regex::new(r"(?i)(bearer\s+)[A-Za-z0-9\-._~+/]+=*").unwrap();
+        // the key, maybe quoted or in `input[...]`, then a quoted or a bare value
+        let field = Regex::new(&format!(
+            r#"(?i)((?:{FIELDS})(?:\\?"|%22|%5D|\])?\s*[=:]\s*)(?:((?:Some\()?\\?")[^"\\]*|[^"\\&\s,)}}]+)"#
+        ))
+        .unwrap();
+
+        (bearer, field)
+    });
+
+    const REDACTED: &str = "${1}${2}<redacted>";
+    match bearer.replace_all(line, REDACTED) {
+        Cow::Borrowed(line) => field.replace_all(line, REDACTED),
+        Cow::Owned(line) => Cow::Owned(field.replace_all(&line, REDACTED).into_owned()),
+    }
+}
+
+/// Writes log lines to stdout through [`redact`]
+struct RedactingWriter;
+
+impl io::Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // the formatter hands over one whole event at a time
+        let line = String::from_utf8_lossy(buf);
+        io::stdout().write_all(redact(&line).as_bytes())?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RedactingWriter {
+    type Writer = RedactingWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter
+    }
+}
+
+/// Whether a client supplied request id can be trusted into our logs
+fn is_valid_request_id(id: &str) -> bool {
+    (1..=64).contains(&id.len())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Middleware assigning request ids and logging every request
+pub struct RequestId;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequestIdMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| is_valid_request_id(id))
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let span = tracing::info_span!(
+            "request",
+            request_id = %id,
+            method = %req.method(),
+            path = %req.path(),
+            server_fn = tracing::field::Empty,
+        );
+        if let Some(name) = server_fn_name(req.path()) {
+            span.record("server_fn", name);
+        }
+
+        let started_at = Instant::now();
+        let service = self.service.clone();
+        let header = HeaderValue::from_str(&id).expect("request id should be a valid header");
+
+        Box::pin(
+            REQUEST_ID.scope(
+                id,
+                async move {
+                    let result = service.call(req).await;
+                    let latency_ms = started_at.elapsed().as_millis() as u64;
+                    match &result {
+                        Ok(res) if res.status().is_server_error() => tracing::error!(
+                            status = res.status().as_u16(),
+                            latency_ms,
+                            "request failed"
+                        ),
+                        Ok(res) => tracing::info!(
+                            status = res.status().as_u16(),
+                            latency_ms,
+                            "request finished"
+                        ),
+                        Err(e) => tracing::error!(latency_ms, "request failed: {e}"),
+                    }
+
+                    result.map(|mut res| {
+                        res.headers_mut()
+                            .insert(HeaderName::from_static(REQUEST_ID_HEADER), header);
+                        res
+                    })
+                }
+                .instrument(span),
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn assert_redacted(line: &str, secret: &str) {
+        let redacted = redact(line);
+        assert!(
+            !redacted.contains(secret),
+            "{secret:?} left in {redacted:?}"
+        );
+        assert!(redacted.contains("<redacted>"), "{redacted:?}");
+    }
+
+    #[test]
+    fn redact_bearer_tokens() {
+        assert_redacted("authorization: Bearer eyJhbGciOi.J9.abc-_", "eyJhbGciOi");
+        assert_redacted("\"authorization\": \"bearer abc+/def==\"", "abc+/def");
+    }
+
+    #[test]
+    fn redact_query_and_form_fields() {
+        assert_redacted("GET /verify-email?token=s3cret&next=/", "s3cret");
+        assert_redacted("token=s3cret&input%5Bcontent%5D=hi", "s3cret");
+        assert_redacted(
+            "input%5Bpassword%5D=hunter2&input%5Busername%5D=a",
+            "hunter2",
+        );
+        assert_redacted("input[new_password]=hunter2", "hunter2");
+    }
+
+    #[test]
+    fn redact_json_and_debug_fields() {
+        assert_redacted(
+            r#"{"access_token":"s3cret","token_type":"bearer"}"#,
+            "s3cret",
+        );
+        assert_redacted(r#"{"password": "hunter 2"}"#, "hunter 2");
+        assert_redacted(r#"{\"client_secret\":\"s3cret\"}"#, "s3cret");
+        assert_redacted(r#"Token { access_token: "s3cret" }"#, "s3cret");
+        assert_redacted(r#"Config { client_secret: Some("s3cret") }"#, "s3cret");
+    }
+
+    #[test]
+    fn keep_everything_else() {
+        let line = "GET /api/get_topics123 status=200 latency_ms=3 token_type=bearer";
+        assert_eq!(redact(line), line);
+        let line = r#"{"username":"alice","content":"password reset works"}"#;
+        assert_eq!(redact(line), line);
+    }
+
+    #[test]
+    fn only_simple_request_ids_are_trusted() {
+        assert!(is_valid_request_id("3f2c-9a_b"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id(&"a".repeat(65)));
+        assert!(!is_valid_request_id("id\nforged log line"));
+        assert!(!is_valid_request_id("a b"));
+    }
+}
@@ -0,0 +1,450 @@
+//! Pictures of options and PDF documents attached to topics.
+//!
+//! `POST /upload` takes a single `file` field as `multipart/form-data` from an
+//! organizer, who authenticates with `Authorization: Bearer <token>` just like the
+//! backend. The type is told from the content, never from the file name or the claimed
+//! content type, and the size is checked against `uploads.max_image_bytes` or
+//! `uploads.max_document_bytes` while the body is still arriving. Pictures are decoded,
+//! which rejects anything that only looks like one, and get a thumbnail.
+//!
+//! Files go through a pluggable [`Storage`], for now [`LocalStorage`] under
+//! `uploads.dir`, and are served from `/uploads/{name}`. Names are random, so they are
+//! cached forever. Saving a topic or template claims the files it refers to, files
+//! nobody claimed within `uploads.orphan_ttl_secs` (a form that was never sent, a
+//! picture that was replaced) are deleted by [`sweep_orphans`]. A file stays once it
+//! has been claimed, even if the topic stops referring to it later.
+//!
+//! Uploads are rate limited like server fns, under the name `upload`.
+
+use crate::api::{is_upload_name, require_role, Attachment, Image, Role, Upload, THUMBNAIL_SIZE};
+use crate::server::config::config;
+use crate::server::rate_limit::{clients, ip_client, too_many_requests, RateLimiter, User};
+use actix_multipart::Multipart;
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use image::{io::Reader as ImageReader, DynamicImage, ImageFormat, ImageOutputFormat};
+use std::{
+    io::{Cursor, ErrorKind},
+    path::PathBuf,
+    sync::OnceLock,
+    time::Duration,
+};
+
+/// Larger pictures are rejected before decoding them, so they cannot eat all memory
+const MAX_IMAGE_DIMENSION: u32 = 10_000;
+
+/// How often [`sweep_orphans`] looks for unclaimed uploads
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Store a new upload, unclaimed
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> anyhow::Result<()>;
+    /// `None` if there is no file called `name`
+    async fn get(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>>;
+    /// Keep `name` for good, nothing happens if it is already claimed or unknown
+    async fn claim(&self, name: &str) -> anyhow::Result<()>;
+    /// Delete the unclaimed uploads stored longer than `older_than` ago, returns how many
+    async fn sweep(&self, older_than: Duration) -> anyhow::Result<usize>;
+}
+
+/// Keeps claimed files in one directory, and the others in `pending/` under it
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn pending(&self) -> PathBuf {
+        self.dir.join("pending")
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, name: &str, bytes: Vec<u8>) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(self.pending()).await?;
+        tokio::fs::write(self.pending().join(name), bytes)
+            .await
+            .with_context(|| format!("cannot store upload {name}"))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        for dir in [self.dir.clone(), self.pending()] {
+            match tokio::fs::read(dir.join(name)).await {
+                Ok(bytes) => return Ok(Some(bytes)),
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).with_context(|| format!("cannot read upload {name}")),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn claim(&self, name: &str) -> anyhow::Result<()> {
+        match tokio::fs::rename(self.pending().join(name), self.dir.join(name)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("cannot claim upload {name}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn sweep(&self, older_than: Duration) -> anyhow::Result<usize> {
+        let mut entries = match tokio::fs::read_dir(self.pending()).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).context("cannot list pending uploads"),
+        };
+        let mut deleted = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let age = entry
+                .metadata()
+                .await?
+                .modified()?
+                .elapsed()
+                .unwrap_or_default();
+            if age > older_than {
+                tokio::fs::remove_file(entry.path())
+                    .await
+                    .with_context(|| format!("cannot delete upload {:?}", entry.file_name()))?;
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
+static STORAGE: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+/// The configured storage
+pub fn storage() -> &'static dyn Storage {
+    STORAGE
+        .get_or_init(|| Box::new(LocalStorage::new(&config().uploads.dir)))
+        .as_ref()
+}
+
+/// Delete unclaimed uploads every [`SWEEP_INTERVAL`], runs until the server stops
+pub async fn sweep_orphans() {
+    let ttl = Duration::from_secs(config().uploads.orphan_ttl_secs);
+    let mut interval = actix_web::rt::time::interval(SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        match storage().sweep(ttl).await {
+            Ok(0) => {}
+            Ok(deleted) => tracing::info!(deleted, "orphaned uploads deleted"),
+            Err(e) => tracing::error!("{e:#}"),
+        }
+    }
+}
+
+/// What an upload turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Image(ImageFormat),
+    Pdf,
+}
+
+impl Kind {
+    /// Sniff the type from the first bytes
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        let kind = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Self::Image(ImageFormat::Png)
+        } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+            Self::Image(ImageFormat::Jpeg)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Self::Image(ImageFormat::Gif)
+        } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            Self::Image(ImageFormat::WebP)
+        } else if bytes.starts_with(b"%PDF-") {
+            Self::Pdf
+        } else {
+            return None;
+        };
+
+        Some(kind)
+    }
+
+    fn max_bytes(&self) -> usize {
+        match self {
+            Self::Image(_) => config().uploads.max_image_bytes,
+            Self::Pdf => config().uploads.max_document_bytes,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Image(format) => format.extensions_str()[0],
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// Content type of a stored file, from the extension it was given by [`upload`]
+fn content_type(name: &str) -> Option<&'static str> {
+    let extension = name.rsplit_once('.')?.1;
+    if extension == "pdf" {
+        return Some("application/pdf");
+    }
+    ImageFormat::from_extension(extension).map(|format| format.to_mime_type())
+}
+
+/// A thumbnail of `bytes`, PNG if it has transparency and JPEG otherwise
+///
+/// Also makes sure the picture can actually be decoded.
+fn thumbnail(bytes: &[u8], format: ImageFormat) -> anyhow::Result<(Vec<u8>, &'static str)> {
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    let mut limits = image::io::Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    reader.limits(limits);
+    let picture = reader.decode().context("not a valid picture")?;
+
+    let thumbnail = picture.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    let mut encoded = Cursor::new(vec![]);
+    let extension = if thumbnail.color().has_alpha() {
+        thumbnail.write_to(&mut encoded, ImageOutputFormat::Png)?;
+        "png"
+    } else {
+        DynamicImage::ImageRgb8(thumbnail.to_rgb8())
+            .write_to(&mut encoded, ImageOutputFormat::Jpeg(80))?;
+        "jpg"
+    };
+
+    Ok((encoded.into_inner(), extension))
+}
+
+/// Register `POST /upload` and `GET /uploads/{name}`
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/upload", web::post().to(upload))
+        .route("/uploads/{name}", web::get().to(download));
+}
+
+fn bad_request(reason: impl Into<String>) -> HttpResponse {
+    HttpResponse::BadRequest().body(reason.into())
+}
+
+async fn upload(req: HttpRequest, mut payload: Multipart) -> HttpResponse {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let Some(token) = token else {
+        return HttpResponse::Unauthorized().body("login required");
+    };
+    let limiter = req.app_data::<web::Data<RateLimiter>>();
+    if let (true, Some(limiter)) = (config().features.rate_limit, limiter) {
+        let user = limiter.resolve(User::Token(token.to_string())).await;
+        let clients = clients(ip_client(&req), Some(&user));
+        if let Err(wait) = limiter.acquire("upload", &clients) {
+            return too_many_requests(wait);
+        }
+    }
+    if let Err(e) = require_role(token, Role::Organizer).await {
+        return HttpResponse::Forbidden().body(e.to_string());
+    }
+
+    let mut field = loop {
+        match payload.try_next().await {
+            Ok(Some(field)) if field.name() == "file" => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return bad_request("no file field"),
+            Err(e) => return bad_request(e.to_string()),
+        }
+    };
+    let name = field
+        .content_disposition()
+        .get_filename()
+        .unwrap_or("upload")
+        .to_string();
+
+    // the limit is only known once the first bytes tell the type
+    let mut bytes = Vec::<u8>::new();
+    let mut kind = None;
+    loop {
+        match field.try_next().await {
+            Ok(Some(chunk)) => bytes.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(e) => return bad_request(e.to_string()),
+        }
+        if kind.is_none() && bytes.len() >= 12 {
+            kind = Kind::detect(&bytes);
+            if kind.is_none() {
+                return bad_request("only PNG, JPEG, GIF, WebP and PDF files are accepted");
+            }
+        }
+        if let Some(kind) = kind {
+            if bytes.len() > kind.max_bytes() {
+                return HttpResponse::PayloadTooLarge()
+                    .body(format!("{name} is larger than {} bytes", kind.max_bytes()));
+            }
+        }
+    }
+    let Some(kind) = kind.or_else(|| Kind::detect(&bytes)) else {
+        return bad_request("only PNG, JPEG, GIF, WebP and PDF files are accepted");
+    };
+
+    let id = uuid::Uuid::new_v4();
+    let stored_name = format!("{id}.{}", kind.extension());
+    let size = bytes.len() as u64;
+    let (bytes, image) = match kind {
+        Kind::Image(format) => {
+            // decoding takes a while, keep it off the async workers
+            let decoded = web::block(move || {
+                let thumbnail = thumbnail(&bytes, format);
+                (bytes, thumbnail)
+            })
+            .await;
+            let (bytes, (thumbnail, extension)) = match decoded {
+                Ok((bytes, Ok(thumbnail))) => (bytes, thumbnail),
+                Ok((_, Err(e))) => return bad_request(format!("{name}: {e:#}")),
+                Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
+            };
+            let thumbnail_name = format!("{id}-thumb.{extension}");
+            if let Err(e) = storage().put(&thumbnail_name, thumbnail).await {
+                tracing::error!("{e:#}");
+                return HttpResponse::InternalServerError().body("cannot store upload");
+            }
+            let image = Image {
+                url: format!("/uploads/{stored_name}"),
+                thumbnail_url: format!("/uploads/{thumbnail_name}"),
+            };
+            (bytes, Some(image))
+        }
+        Kind::Pdf => (bytes, None),
+    };
+    if let Err(e) = storage().put(&stored_name, bytes).await {
+        tracing::error!("{e:#}");
+        return HttpResponse::InternalServerError().body("cannot store upload");
+    }
+    tracing::info!(name, stored_name, size, "upload stored");
+
+    HttpResponse::Ok().json(Upload {
+        attachment: Attachment {
+            url: format!("/uploads/{stored_name}"),
+            name,
+            size,
+        },
+        image,
+    })
+}
+
+async fn download(name: web::Path<String>) -> HttpResponse {
+    let name = name.into_inner();
+    let (true, Some(content_type)) = (is_upload_name(&name), content_type(&name)) else {
+        return HttpResponse::NotFound().finish();
+    };
+
+    match storage().get(&name).await {
+        Ok(Some(bytes)) => {
+            let mut resp = HttpResponse::Ok();
+            resp.content_type(content_type)
+                .insert_header((header::CACHE_CONTROL, "public, max-age=31536000, immutable"));
+            // documents are downloaded, never rendered by this site
+            if content_type == "application/pdf" {
+                resp.insert_header((header::CONTENT_DISPOSITION, "attachment"));
+            }
+            resp.body(bytes)
+        }
+        Ok(None) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("{e:#}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png() -> Vec<u8> {
+        let mut bytes = Cursor::new(vec![]);
+        DynamicImage::new_rgb8(2, 2)
+            .write_to(&mut bytes, ImageOutputFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn detect_kind_from_content() {
+        assert_eq!(Kind::detect(&png()), Some(Kind::Image(ImageFormat::Png)));
+        assert_eq!(
+            Kind::detect(b"\xff\xd8\xff\xe0\x00\x10JFIF"),
+            Some(Kind::Image(ImageFormat::Jpeg))
+        );
+        assert_eq!(
+            Kind::detect(b"GIF89a\x01\x00"),
+            Some(Kind::Image(ImageFormat::Gif))
+        );
+        assert_eq!(
+            Kind::detect(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some(Kind::Image(ImageFormat::WebP))
+        );
+        assert_eq!(Kind::detect(b"%PDF-1.7\n"), Some(Kind::Pdf));
+
+        assert_eq!(Kind::detect(b"<html><script>alert(1)</script>"), None);
+        assert_eq!(
+            Kind::detect(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+            None
+        );
+        assert_eq!(Kind::detect(b"RIFF\x24\x00\x00\x00WAVEfmt "), None);
+        assert_eq!(Kind::detect(b""), None);
+    }
+
+    #[test]
+    fn polyglot_pictures_are_rejected_when_decoded() {
+        // a GIF header followed by script, valid JavaScript as well
+        let polyglot = b"GIF89a/*\x01\x00\x01\x00*/=1;alert(document.cookie)//";
+        let Some(Kind::Image(format)) = Kind::detect(polyglot) else {
+            panic!("should look like a picture");
+        };
+
+        assert!(thumbnail(polyglot, format).is_err());
+        let (_, extension) = thumbnail(&png(), ImageFormat::Png).unwrap();
+        assert_eq!(extension, "jpg");
+    }
+
+    #[test]
+    fn content_type_from_stored_extension() {
+        assert_eq!(content_type("1a2b.png"), Some("image/png"));
+        assert_eq!(content_type("1a2b-thumb.jpg"), Some("image/jpeg"));
+        assert_eq!(content_type("1a2b.webp"), Some("image/webp"));
+        assert_eq!(content_type("1a2b.pdf"), Some("application/pdf"));
+        assert_eq!(content_type("1a2b.html"), None);
+        assert_eq!(content_type("1a2b.svg"), None);
+        assert_eq!(content_type("png"), None);
+        // passes here, `is_upload_name` keeps it from being looked up
+        assert_eq!(content_type("..png"), Some("image/png"));
+        assert!(!is_upload_name("..png"));
+    }
+
+    #[actix_web::test]
+    async fn unclaimed_uploads_are_swept() {
+        let dir = std::env::temp_dir().join(format!("vote-uploads-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&dir);
+        storage.put("kept.png", vec![1]).await.unwrap();
+        storage.put("orphan.png", vec![2]).await.unwrap();
+        storage.claim("kept.png").await.unwrap();
+        // claiming twice or an unknown file is fine
+        storage.claim("kept.png").await.unwrap();
+        storage.claim("unknown.png").await.unwrap();
+
+        assert_eq!(storage.sweep(Duration::from_secs(60)).await.unwrap(), 0);
+        assert_eq!(storage.get("orphan.png").await.unwrap(), Some(vec![2]));
+        assert_eq!(storage.sweep(Duration::ZERO).await.unwrap(), 1);
+        assert_eq!(storage.get("orphan.png").await.unwrap(), None);
+        assert_eq!(storage.get("kept.png").await.unwrap(), Some(vec![1]));
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+}
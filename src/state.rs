@@ -1,8 +1,10 @@
+use crate::api::{Role, User};
 use leptos::*;
 
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct GlobalState {
     token: Option<String>,
+    user: Option<User>,
 }
 
 impl GlobalState {
@@ -21,4 +23,22 @@ impl GlobalState {
     pub fn set_token(&mut self, token: String) {
         self.token = Some(token);
     }
+
+    pub fn user(&self) -> Option<&User> {
+        self.user.as_ref()
+    }
+
+    pub fn set_user(&mut self, user: Option<User>) {
+        self.user = user;
+    }
+
+    /// Role of the current user, `None` if not logged in or not loaded yet
+    pub fn role(&self) -> Option<Role> {
+        self.user.as_ref().map(|u| u.role)
+    }
+
+    /// Whether the current user has at least the `required` role
+    pub fn has_role(&self, required: Role) -> bool {
+        self.role().map_or(false, |r| r.allows(required))
+    }
 }
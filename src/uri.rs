@@ -0,0 +1,46 @@
+//! Percent-encoding of URL path segments and query values
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// Everything but the unreserved characters of RFC 3986
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Encode `value` to be put in a path segment or a query value as it is
+pub fn encode_component(value: &str) -> String {
+    utf8_percent_encode(value, COMPONENT).to_string()
+}
+
+/// Decode a percent-encoded path segment or query value, invalid UTF-8 is replaced
+pub fn decode_component(value: &str) -> String {
+    percent_decode_str(value).decode_utf8_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_reserved_and_non_ascii() {
+        assert_eq!(encode_component("rust-lang_2.0~"), "rust-lang_2.0~");
+        assert_eq!(
+            encode_component("a b&c=d?e#f+g%/"),
+            "a%20b%26c%3Dd%3Fe%23f%2Bg%25%2F"
+        );
+        assert_eq!(encode_component("投票"), "%E6%8A%95%E7%A5%A8");
+    }
+
+    #[test]
+    fn decode_what_was_encoded() {
+        for value in ["", "/topic/1?page=2#c", "投票 100%", "%zz"] {
+            assert_eq!(decode_component(&encode_component(value)), value);
+        }
+        assert_eq!(decode_component("%E6%8A%95%E7%A5%A8"), "投票");
+        // left as it is when not followed by two hex digits
+        assert_eq!(decode_component("100%"), "100%");
+        assert_eq!(decode_component("%FF"), "\u{FFFD}");
+    }
+}
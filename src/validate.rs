@@ -0,0 +1,114 @@
+//! Form validation rules shared by the client (live feedback) and the server fns
+
+/// A broken rule, the server fns send its [`Invalid::message_id`] for the client to translate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invalid {
+    UsernameLength,
+    UsernameStart,
+    UsernameChars,
+    Email,
+    PasswordLength,
+    PasswordWeak,
+}
+
+impl Invalid {
+    /// Id of the message in the catalogs under `locales/`
+    pub fn message_id(self) -> &'static str {
+        match self {
+            Invalid::UsernameLength => "invalid-username-length",
+            Invalid::UsernameStart => "invalid-username-start",
+            Invalid::UsernameChars => "invalid-username-chars",
+            Invalid::Email => "invalid-email",
+            Invalid::PasswordLength => "invalid-password-length",
+            Invalid::PasswordWeak => "invalid-password-weak",
+        }
+    }
+}
+
+/// Check the username rules, returns the broken one
+pub fn validate_username(username: &str) -> Result<(), Invalid> {
+    let len = username.chars().count();
+    if !(3..=32).contains(&len) {
+        return Err(Invalid::UsernameLength);
+    }
+    if !username.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(Invalid::UsernameStart);
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(Invalid::UsernameChars);
+    }
+
+    Ok(())
+}
+
+/// A rough check, the backend is the one that actually sends mail to it
+pub fn validate_email(email: &str) -> Result<(), Invalid> {
+    match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => Ok(()),
+        _ => Err(Invalid::Email),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PasswordStrength {
+    Weak,
+    Fair,
+    Good,
+    Strong,
+}
+
+impl PasswordStrength {
+    /// Id of the label in the catalogs under `locales/`
+    pub fn message_id(self) -> &'static str {
+        match self {
+            PasswordStrength::Weak => "password-weak",
+            PasswordStrength::Fair => "password-fair",
+            PasswordStrength::Good => "password-good",
+            PasswordStrength::Strong => "password-strong",
+        }
+    }
+
+    /// Score from 1 (weak) to 4 (strong), used by the strength meter
+    pub fn score(self) -> u8 {
+        self as u8 + 1
+    }
+}
+
+/// Estimate password strength from its length and the kinds of characters used
+pub fn password_strength(password: &str) -> PasswordStrength {
+    let len = password.chars().count();
+    if len < 8 {
+        return PasswordStrength::Weak;
+    }
+
+    let classes = [
+        password.chars().any(|c| c.is_ascii_lowercase()),
+        password.chars().any(|c| c.is_ascii_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_ascii_alphanumeric()),
+    ]
+    .into_iter()
+    .filter(|&used| used)
+    .count();
+
+    match (classes, len) {
+        (4, _) | (3, 12..) => PasswordStrength::Strong,
+        (3, _) | (2, 12..) => PasswordStrength::Good,
+        (2, _) | (_, 16..) => PasswordStrength::Fair,
+        _ => PasswordStrength::Weak,
+    }
+}
+
+pub fn validate_password(password: &str) -> Result<(), Invalid> {
+    if password.chars().count() < 8 {
+        return Err(Invalid::PasswordLength);
+    }
+    if password_strength(password) < PasswordStrength::Fair {
+        return Err(Invalid::PasswordWeak);
+    }
+
+    Ok(())
+}
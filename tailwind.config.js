@@ -0,0 +1,14 @@
+/** @type {import('tailwindcss').Config} */
+module.exports = {
+  content: {
+    files: ["./src/**/*.rs"],
+    transform: {
+      // `class:btn-primary=...` in `view!` toggles `btn-primary`
+      rs: (content) => content.replace(/class:([\w-]+)=/g, " $1 "),
+    },
+  },
+  theme: {
+    extend: {},
+  },
+  plugins: [require("daisyui")],
+};